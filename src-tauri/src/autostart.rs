@@ -0,0 +1,83 @@
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+use crate::settings;
+
+/// Passed to login launches so the app can start hidden in the tray.
+pub const MINIMIZED_ARG: &str = "--minimized";
+/// Same as [`MINIMIZED_ARG`], for launchers configured by hand.
+const HIDDEN_ARG: &str = "--hidden";
+
+/// Whether the app was launched with `--minimized` or `--hidden`.
+pub fn starts_hidden<I: IntoIterator<Item = String>>(args: I) -> bool {
+    args.into_iter()
+        .skip(1)
+        .any(|arg| arg == MINIMIZED_ARG || arg == HIDDEN_ARG)
+}
+
+pub fn plugin<R: Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![MINIMIZED_ARG]))
+}
+
+/// Enables or disables the OS login item, skipping the call when it already
+/// matches so repeated toggles never register a second entry.
+fn apply(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let autolaunch = app.autolaunch();
+    if autolaunch.is_enabled().map_err(|e| e.to_string())? == enabled {
+        return Ok(());
+    }
+    if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Brings the OS login item in line with the stored preference, in case it
+/// was removed or added outside the app.
+pub fn reconcile(app: &AppHandle) {
+    let Some(enabled) = settings::get::<bool>(app, settings::AUTOSTART) else {
+        return;
+    };
+    if let Err(e) = apply(app, enabled) {
+        log::warn!("failed to sync the launch-at-login item: {e}");
+    }
+}
+
+#[tauri::command]
+pub fn set_autostart(app: AppHandle, enabled: bool) -> Result<(), String> {
+    apply(&app, enabled)?;
+    settings::update(&app, settings::AUTOSTART, enabled)
+}
+
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<bool, String> {
+    app.autolaunch().is_enabled().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_hidden_launches() {
+        assert!(starts_hidden(args(&["bolt-gives", "--minimized"])));
+        assert!(starts_hidden(args(&[
+            "bolt-gives",
+            "boltgives://open",
+            "--hidden"
+        ])));
+        assert!(!starts_hidden(args(&["bolt-gives"])));
+        assert!(!starts_hidden(args(&[
+            "bolt-gives",
+            "boltgives://open?x=--hidden"
+        ])));
+        // The program path is never an argument.
+        assert!(!starts_hidden(args(&["--hidden"])));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::{main_window, persistence, settings, shortcut, shutdown};
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const MAX_INTERVAL_HOURS: u64 = 24 * 365;
+const MAX_BACKUPS: usize = 3;
+
+const SETTINGS_RESTORED_EVENT: &str = "settings-restored";
+
+fn backup_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("app-data.backup.{index}.json"))
+}
+
+/// Copies the store file in `dir` to backup 1, shifting older backups up and
+/// dropping any past `max_backups`. Nothing is written when the store is
+/// missing or identical to the newest backup; returns whether a backup was made.
+fn rotate_backups(dir: &Path, max_backups: usize) -> io::Result<bool> {
+    let contents = match fs::read(dir.join(settings::STORE_PATH)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if max_backups == 0 || fs::read(backup_path(dir, 1)).is_ok_and(|newest| newest == contents) {
+        return Ok(false);
+    }
+
+    let oldest = backup_path(dir, max_backups);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..max_backups).rev() {
+        let backup = backup_path(dir, index);
+        if backup.exists() {
+            fs::rename(&backup, backup_path(dir, index + 1))?;
+        }
+    }
+    fs::write(backup_path(dir, 1), contents)?;
+    Ok(true)
+}
+
+fn backup(app: &AppHandle) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    // Back up what is in memory, not a file that is still waiting on a save.
+    settings::flush(app)?;
+    if rotate_backups(&dir, MAX_BACKUPS).map_err(|e| e.to_string())? {
+        log::info!("backed up the settings store");
+    }
+    Ok(())
+}
+
+/// How often to back up for `backup-interval-hours`, capped at a year since
+/// the setting can hold any number; `None` for `0`, which turns backups off.
+fn backup_interval(hours: u64) -> Option<Duration> {
+    let hours = hours.min(MAX_INTERVAL_HOURS);
+    (hours > 0).then(|| Duration::from_secs(hours.saturating_mul(60 * 60)))
+}
+
+/// Backs up the store on launch and then every `backup-interval-hours`
+/// (daily by default); `0` turns backups off.
+pub async fn run(app: AppHandle) {
+    let hours = settings::get::<u64>(&app, settings::BACKUP_INTERVAL_HOURS)
+        .unwrap_or(DEFAULT_INTERVAL_HOURS);
+    let Some(period) = backup_interval(hours) else {
+        return;
+    };
+    let mut stop = shutdown::subscribe(&app);
+    let mut interval = tokio::time::interval(period);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Err(e) = backup(&app) {
+                    log::warn!("failed to back up settings: {e}");
+                }
+            }
+            _ = shutdown::requested(&mut stop) => return,
+        }
+    }
+}
+
+/// Replaces the store with backup `index`, where `1` is the newest, and
+/// reloads it.
+#[tauri::command]
+pub fn restore_backup(app: AppHandle, index: usize) -> Result<(), AppError> {
+    if !(1..=MAX_BACKUPS).contains(&index) {
+        return Err(AppError::InvalidInput(format!(
+            "backup index must be between 1 and {MAX_BACKUPS}"
+        )));
+    }
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let contents = fs::read(backup_path(&dir, index))?;
+    if !matches!(serde_json::from_slice(&contents), Ok(Value::Object(_))) {
+        return Err(AppError::Store(format!(
+            "backup {index} is not a valid settings file"
+        )));
+    }
+
+    let before = settings::entries(&app);
+    let previous_shortcut = shortcut::toggle_shortcut(&app);
+    fs::write(dir.join(settings::STORE_PATH), contents)?;
+    persistence::store(&app).reload().map_err(AppError::Store)?;
+    log::info!("restored settings from backup {index}");
+
+    settings::broadcast_changes(&app, &before);
+    settings::reapply(&app, previous_shortcut).map_err(AppError::Window)?;
+    let _ = app.emit_to(main_window::LABEL, SETTINGS_RESTORED_EVENT, index);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caps_the_backup_interval() {
+        assert_eq!(backup_interval(0), None);
+        assert_eq!(backup_interval(2), Some(Duration::from_secs(2 * 60 * 60)));
+        assert_eq!(
+            backup_interval(u64::MAX),
+            Some(Duration::from_secs(MAX_INTERVAL_HOURS * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn rotates_backups_and_skips_unchanged_stores() {
+        let dir = std::env::temp_dir().join(format!("bolt-gives-backup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let store = dir.join(settings::STORE_PATH);
+
+        // No store yet: nothing to back up.
+        assert!(!rotate_backups(&dir, 3).unwrap());
+
+        for version in 1..=4 {
+            fs::write(&store, format!("{{\"v\":{version}}}")).unwrap();
+            assert!(rotate_backups(&dir, 3).unwrap());
+            assert!(!rotate_backups(&dir, 3).unwrap());
+        }
+
+        let read = |index| fs::read_to_string(backup_path(&dir, index)).unwrap();
+        assert_eq!(read(1), "{\"v\":4}");
+        assert_eq!(read(2), "{\"v\":3}");
+        assert_eq!(read(3), "{\"v\":2}");
+        assert!(!backup_path(&dir, 4).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
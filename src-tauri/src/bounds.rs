@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Window};
+
+use crate::error::AppError;
+use crate::{main_window, safe_mode, settings, splash};
+
+/// Minimum gap between bounds writes while the user drags or resizes.
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Smallest saved size, in physical pixels, that is still restored.
+const MIN_WIDTH: u32 = 400;
+const MIN_HEIGHT: u32 = 300;
+
+/// Anything past this is a corrupt value (a negative size cast to `u32`, say)
+/// rather than a real window.
+const MAX_DIMENSION: u32 = 16_384;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    /// Scale factor of the monitor the bounds were saved on; missing in bounds
+    /// saved by older versions.
+    #[serde(default)]
+    pub scale_factor: Option<f64>,
+}
+
+/// A display and its work area (the part not taken by taskbars and docks), in
+/// physical pixels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+}
+
+impl From<&Monitor> for MonitorInfo {
+    fn from(monitor: &Monitor) -> Self {
+        let area = monitor.work_area();
+        Self {
+            name: monitor.name().cloned(),
+            x: area.position.x,
+            y: area.position.y,
+            width: area.size.width,
+            height: area.size.height,
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Tracks the latest scheduled bounds write per window so only the last one in
+/// a burst hits disk.
+#[derive(Default)]
+pub struct BoundsWriter {
+    generations: Mutex<HashMap<String, u64>>,
+}
+
+/// Main window bounds left by the previous instance when it restarted itself.
+/// They are moved out of the store at startup so only one launch uses them.
+#[derive(Default)]
+pub struct WarmRestart(Mutex<Option<WindowBounds>>);
+
+/// Store key for the bounds of the window labelled `label`. The main window
+/// keeps the original unscoped key.
+pub fn store_key(label: &str) -> String {
+    if label == main_window::LABEL {
+        settings::WINDOW_BOUNDS.to_string()
+    } else {
+        format!("{}/{label}", settings::WINDOW_BOUNDS)
+    }
+}
+
+/// The splash is fixed-size and short-lived; every other window is tracked,
+/// except in safe mode, whose default geometry would overwrite the saved one.
+fn tracks(window: &Window) -> bool {
+    window.label() != splash::LABEL && !safe_mode::is_active(window.app_handle())
+}
+
+/// Whether saved bounds describe a window worth restoring. Rejected bounds
+/// leave the window at its configured default size.
+fn is_usable(bounds: &WindowBounds) -> bool {
+    (MIN_WIDTH..=MAX_DIMENSION).contains(&bounds.width)
+        && (MIN_HEIGHT..=MAX_DIMENSION).contains(&bounds.height)
+}
+
+/// Applies the persisted bounds to `window`, if any were saved. After a warm
+/// restart the main window gets the exact bounds it had before, unclamped.
+pub fn restore(window: &Window) -> Result<(), String> {
+    if window.label() == main_window::LABEL {
+        let warm = window.state::<WarmRestart>().0.lock().unwrap().take();
+        if let Some(bounds) = warm.filter(is_usable) {
+            return set_geometry(window, &bounds);
+        }
+    }
+    let key = store_key(window.label());
+    let Some(bounds) = settings::get::<WindowBounds>(window.app_handle(), &key) else {
+        return Ok(());
+    };
+    apply(window, &bounds)
+}
+
+/// Moves and resizes `window` to `bounds`, clamped to the connected monitors.
+/// Unusable sizes are ignored.
+pub fn apply(window: &Window, bounds: &WindowBounds) -> Result<(), String> {
+    if !is_usable(bounds) {
+        log::warn!(
+            "ignoring saved {}x{} bounds for \"{}\"",
+            bounds.width,
+            bounds.height,
+            window.label()
+        );
+        return Ok(());
+    }
+    let monitors = monitors(window);
+    set_geometry(
+        window,
+        &clamp_bounds(&rescale_for(bounds, &monitors), &monitors),
+    )
+}
+
+/// Moves and resizes `window` to exactly `bounds`, then re-applies the
+/// maximized or fullscreen state.
+fn set_geometry(window: &Window, bounds: &WindowBounds) -> Result<(), String> {
+    // The geometry only takes effect on a restored window.
+    if window.is_fullscreen().unwrap_or(false) {
+        window.set_fullscreen(false).map_err(|e| e.to_string())?;
+    }
+    if window.is_maximized().unwrap_or(false) {
+        window.unmaximize().map_err(|e| e.to_string())?;
+    }
+    window
+        .set_position(PhysicalPosition::new(bounds.x, bounds.y))
+        .map_err(|e| e.to_string())?;
+    window
+        .set_size(PhysicalSize::new(bounds.width, bounds.height))
+        .map_err(|e| e.to_string())?;
+
+    if bounds.fullscreen {
+        window.set_fullscreen(true).map_err(|e| e.to_string())?;
+    } else if bounds.maximized {
+        window.maximize().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Lists the work areas of all connected displays, with the window's current
+/// (or the primary) monitor first so it is used as the fallback.
+fn monitors(window: &Window) -> Vec<MonitorInfo> {
+    let fallback = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .or_else(|| window.primary_monitor().ok().flatten());
+    let available = window.available_monitors().unwrap_or_default();
+    fallback
+        .iter()
+        .chain(available.iter())
+        .map(MonitorInfo::from)
+        .collect()
+}
+
+/// Area of `bounds` that falls on `monitor`, in square physical pixels.
+fn overlap(bounds: &WindowBounds, monitor: &MonitorInfo) -> i64 {
+    let left = i64::from(bounds.x).max(i64::from(monitor.x));
+    let top = i64::from(bounds.y).max(i64::from(monitor.y));
+    let right = (i64::from(bounds.x) + i64::from(bounds.width))
+        .min(i64::from(monitor.x) + i64::from(monitor.width));
+    let bottom = (i64::from(bounds.y) + i64::from(bounds.height))
+        .min(i64::from(monitor.y) + i64::from(monitor.height));
+    (right - left).max(0) * (bottom - top).max(0)
+}
+
+/// The monitor holding most of `bounds`, or the first one if it is on none.
+fn main_monitor<'a>(bounds: &WindowBounds, monitors: &'a [MonitorInfo]) -> Option<&'a MonitorInfo> {
+    monitors
+        .iter()
+        .filter(|monitor| overlap(bounds, monitor) > 0)
+        .max_by_key(|monitor| overlap(bounds, monitor))
+        .or_else(|| monitors.first())
+}
+
+fn intersects(bounds: &WindowBounds, monitor: &MonitorInfo) -> bool {
+    let (left, top) = (i64::from(bounds.x), i64::from(bounds.y));
+    let (right, bottom) = (
+        left + i64::from(bounds.width),
+        top + i64::from(bounds.height),
+    );
+    let (m_left, m_top) = (i64::from(monitor.x), i64::from(monitor.y));
+    let (m_right, m_bottom) = (
+        m_left + i64::from(monitor.width),
+        m_top + i64::from(monitor.height),
+    );
+    left < m_right && right > m_left && top < m_bottom && bottom > m_top
+}
+
+/// Converts the size in `bounds` from the scale factor it was saved at to
+/// `scale_factor`, keeping the same logical size. Bounds without a saved scale
+/// factor are returned as they are.
+fn rescale(bounds: &WindowBounds, scale_factor: f64) -> WindowBounds {
+    let Some(saved) = bounds.scale_factor.filter(|saved| *saved > 0.0) else {
+        return *bounds;
+    };
+    if (saved - scale_factor).abs() < f64::EPSILON || scale_factor <= 0.0 {
+        return *bounds;
+    }
+    let convert = |physical: u32| (f64::from(physical) / saved * scale_factor).round() as u32;
+    WindowBounds {
+        width: convert(bounds.width),
+        height: convert(bounds.height),
+        scale_factor: Some(scale_factor),
+        ..*bounds
+    }
+}
+
+/// Rescales `bounds` for the monitor it will be shown on: the one it overlaps,
+/// or the fallback that [`clamp_bounds`] will center it on.
+fn rescale_for(bounds: &WindowBounds, monitors: &[MonitorInfo]) -> WindowBounds {
+    let target = monitors
+        .iter()
+        .find(|monitor| intersects(bounds, monitor))
+        .or_else(|| monitors.first());
+    match target {
+        Some(monitor) => rescale(bounds, monitor.scale_factor),
+        None => *bounds,
+    }
+}
+
+/// Keeps saved bounds usable on the current display setup.
+///
+/// Bounds that no longer touch any monitor (e.g. after undocking) are centered
+/// on the first monitor in `monitors`, and the size is shrunk to fit the
+/// monitor the window ends up on.
+pub fn clamp_bounds(bounds: &WindowBounds, monitors: &[MonitorInfo]) -> WindowBounds {
+    let Some(fallback) = monitors.first() else {
+        return *bounds;
+    };
+    let visible_on = monitors.iter().find(|monitor| intersects(bounds, monitor));
+    let monitor = visible_on.unwrap_or(fallback);
+
+    let width = bounds.width.min(monitor.width);
+    let height = bounds.height.min(monitor.height);
+    let (x, y) = match visible_on {
+        Some(_) => (bounds.x, bounds.y),
+        None => (
+            monitor.x + ((monitor.width - width) / 2) as i32,
+            monitor.y + ((monitor.height - height) / 2) as i32,
+        ),
+    };
+
+    WindowBounds {
+        x,
+        y,
+        width,
+        height,
+        ..*bounds
+    }
+}
+
+/// `bounds` resized for `monitor` and centered in its work area.
+fn center_on(bounds: &WindowBounds, monitor: &MonitorInfo) -> WindowBounds {
+    let bounds = rescale(bounds, monitor.scale_factor);
+    let width = bounds.width.min(monitor.width);
+    let height = bounds.height.min(monitor.height);
+    WindowBounds {
+        x: monitor.x + ((monitor.width - width) / 2) as i32,
+        y: monitor.y + ((monitor.height - height) / 2) as i32,
+        width,
+        height,
+        ..bounds
+    }
+}
+
+/// Connected displays, in the order [`move_to_monitor`] indexes them.
+#[tauri::command]
+pub fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, AppError> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    Ok(monitors.iter().map(MonitorInfo::from).collect())
+}
+
+/// Centers the main window on the monitor at `index` in [`list_monitors`] and
+/// saves the new bounds, so it reopens there.
+#[tauri::command]
+pub fn move_to_monitor(app: AppHandle, index: usize) -> Result<(), AppError> {
+    let monitors = list_monitors(app.clone())?;
+    let Some(monitor) = monitors.get(index) else {
+        return Err(AppError::InvalidInput(format!(
+            "there is no monitor {index}; {} connected",
+            monitors.len()
+        )));
+    };
+    let window = main_window::window(&app)?;
+    center_window_on(&window, monitor)
+}
+
+fn center_window_on(window: &Window, monitor: &MonitorInfo) -> Result<(), AppError> {
+    let bounds = capture(window).map_err(AppError::Window)?;
+    apply(window, &center_on(&bounds, monitor)).map_err(AppError::Window)?;
+    save(window).map_err(AppError::Store)
+}
+
+/// Centers the main window on the monitor that holds most of it and saves the
+/// new bounds.
+#[tauri::command]
+pub fn center_window(app: AppHandle) -> Result<(), AppError> {
+    let window = main_window::window(&app)?;
+    let bounds = capture(&window).map_err(AppError::Window)?;
+    let monitors = monitors(&window);
+    let monitor = main_monitor(&bounds, &monitors)
+        .ok_or_else(|| AppError::Window("no monitor is connected".to_string()))?;
+    center_window_on(&window, monitor)
+}
+
+/// Captures the current geometry of `window` into the store.
+pub fn save(window: &Window) -> Result<(), String> {
+    if !tracks(window) {
+        return Ok(());
+    }
+    let bounds = capture(window)?;
+    settings::set(window.app_handle(), &store_key(window.label()), bounds)
+}
+
+/// Saves the main window's bounds ahead of `restart()` and marks them for the
+/// relaunched instance to restore as they are.
+pub fn prepare_restart(app: &AppHandle) {
+    if safe_mode::is_active(app) {
+        return;
+    }
+    let Some(window) = app.get_webview_window(main_window::LABEL) else {
+        return;
+    };
+    let result = capture(&window.as_ref().window()).and_then(|bounds| {
+        settings::set(app, settings::WINDOW_BOUNDS, bounds)?;
+        settings::set(app, settings::WARM_RESTART_BOUNDS, bounds)
+    });
+    if let Err(e) = result {
+        log::warn!("failed to save the window bounds before restarting: {e}");
+    }
+}
+
+/// Takes the bounds left by [`prepare_restart`] out of the store, so a launch
+/// that never gets to restore them doesn't leave them for a later cold start.
+pub fn take_warm_restart(app: &AppHandle) {
+    let bounds = settings::get::<WindowBounds>(app, settings::WARM_RESTART_BOUNDS);
+    if let Err(e) = settings::remove(app, settings::WARM_RESTART_BOUNDS) {
+        log::warn!("failed to clear the warm restart bounds: {e}");
+    }
+    *app.state::<WarmRestart>().0.lock().unwrap() = bounds;
+}
+
+/// Reads the current geometry of `window`.
+pub fn capture(window: &Window) -> Result<WindowBounds, String> {
+    let app = window.app_handle();
+    let key = store_key(window.label());
+    let maximized = window.is_maximized().map_err(|e| e.to_string())?;
+    let fullscreen = window.is_fullscreen().map_err(|e| e.to_string())?;
+
+    // While maximized/fullscreen the physical bounds are the monitor's, so keep
+    // the last restored geometry and only flip the flags.
+    let previous = settings::get::<WindowBounds>(app, &key);
+    let scale_factor = window.scale_factor().ok();
+    let bounds = match previous {
+        Some(previous) if maximized || fullscreen => WindowBounds {
+            maximized,
+            fullscreen,
+            ..previous
+        },
+        _ => {
+            let position = window.outer_position().map_err(|e| e.to_string())?;
+            let size = window.inner_size().map_err(|e| e.to_string())?;
+            WindowBounds {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+                fullscreen,
+                scale_factor,
+            }
+        }
+    };
+    Ok(bounds)
+}
+
+/// Saves the bounds of `window` once it has stopped moving for [`SAVE_DEBOUNCE`].
+pub fn schedule_save(window: &Window) {
+    if !tracks(window) || window.is_minimized().unwrap_or(false) {
+        return;
+    }
+
+    let label = window.label().to_string();
+    let generation = {
+        let writer = window.state::<BoundsWriter>();
+        let mut generations = writer.generations.lock().unwrap();
+        let generation = generations.entry(label.clone()).or_default();
+        *generation += 1;
+        *generation
+    };
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        let latest = window
+            .state::<BoundsWriter>()
+            .generations
+            .lock()
+            .unwrap()
+            .get(&label)
+            .copied();
+        if latest == Some(generation) {
+            let _ = save(&window);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAPTOP: MonitorInfo = MonitorInfo {
+        name: None,
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+        scale_factor: 2.0,
+    };
+    const EXTERNAL: MonitorInfo = MonitorInfo {
+        name: None,
+        x: 1920,
+        y: 0,
+        width: 3840,
+        height: 2160,
+        scale_factor: 1.0,
+    };
+
+    fn bounds(x: i32, y: i32, width: u32, height: u32) -> WindowBounds {
+        WindowBounds {
+            x,
+            y,
+            width,
+            height,
+            maximized: false,
+            fullscreen: false,
+            scale_factor: None,
+        }
+    }
+
+    #[test]
+    fn keeps_bounds_that_are_visible() {
+        let saved = bounds(2000, 100, 1280, 832);
+        assert_eq!(clamp_bounds(&saved, &[LAPTOP, EXTERNAL]), saved);
+    }
+
+    #[test]
+    fn centers_on_fallback_when_monitor_is_gone() {
+        let saved = bounds(2000, 100, 1280, 832);
+        assert_eq!(clamp_bounds(&saved, &[LAPTOP]), bounds(320, 124, 1280, 832));
+    }
+
+    #[test]
+    fn shrinks_to_fit_smaller_monitor() {
+        let saved = bounds(3000, 200, 3000, 1800);
+        assert_eq!(clamp_bounds(&saved, &[LAPTOP]), bounds(0, 0, 1920, 1080));
+    }
+
+    #[test]
+    fn leaves_bounds_alone_without_monitors() {
+        let saved = bounds(-5000, -5000, 1280, 832);
+        assert_eq!(clamp_bounds(&saved, &[]), saved);
+    }
+
+    #[test]
+    fn preserves_window_state_flags() {
+        let saved = WindowBounds {
+            maximized: true,
+            ..bounds(9000, 9000, 1280, 832)
+        };
+        assert!(clamp_bounds(&saved, &[LAPTOP]).maximized);
+    }
+
+    #[test]
+    fn rejects_unusable_sizes() {
+        assert!(is_usable(&bounds(0, 0, 1280, 832)));
+        assert!(is_usable(&bounds(0, 0, MIN_WIDTH, MIN_HEIGHT)));
+        assert!(!is_usable(&bounds(0, 0, 0, 832)));
+        assert!(!is_usable(&bounds(0, 0, 1280, 0)));
+        assert!(!is_usable(&bounds(0, 0, 399, 832)));
+        assert!(!is_usable(&bounds(0, 0, -1i32 as u32, 832)));
+        assert!(!is_usable(&bounds(0, 0, 1280, -600i32 as u32)));
+        assert!(!is_usable(&bounds(0, 0, MAX_DIMENSION + 1, 832)));
+    }
+
+    #[test]
+    fn rescales_between_monitor_densities() {
+        let on_retina = WindowBounds {
+            scale_factor: Some(2.0),
+            ..bounds(2000, 100, 2560, 1664)
+        };
+        // Same 1280x832 logical size on a 1x display.
+        assert_eq!(
+            rescale(&on_retina, 1.0),
+            WindowBounds {
+                scale_factor: Some(1.0),
+                ..bounds(2000, 100, 1280, 832)
+            }
+        );
+
+        let at_150 = WindowBounds {
+            scale_factor: Some(1.5),
+            ..bounds(0, 0, 1500, 900)
+        };
+        assert_eq!(rescale(&at_150, 2.0).width, 2000);
+        assert_eq!(rescale(&at_150, 2.0).height, 1200);
+        assert_eq!(rescale(&at_150, 1.5), at_150);
+    }
+
+    #[test]
+    fn keeps_physical_size_without_a_saved_scale() {
+        let legacy = bounds(0, 0, 1280, 832);
+        assert_eq!(rescale(&legacy, 2.0), legacy);
+    }
+
+    #[test]
+    fn rescales_for_the_monitor_the_window_lands_on() {
+        let saved = WindowBounds {
+            scale_factor: Some(1.0),
+            ..bounds(100, 100, 1280, 832)
+        };
+        // Overlaps the 2x laptop panel.
+        assert_eq!(rescale_for(&saved, &[EXTERNAL, LAPTOP]).width, 2560);
+        // Off every monitor: rescaled for the fallback it gets centered on.
+        let lost = WindowBounds { x: -9000, ..saved };
+        assert_eq!(rescale_for(&lost, &[EXTERNAL, LAPTOP]).width, 1280);
+    }
+
+    #[test]
+    fn picks_the_monitor_holding_most_of_the_window() {
+        // Straddling the edge with most of it on the external display.
+        let straddling = bounds(1500, 100, 1280, 832);
+        assert_eq!(
+            main_monitor(&straddling, &[LAPTOP, EXTERNAL]),
+            Some(&EXTERNAL)
+        );
+        let mostly_laptop = bounds(1000, 100, 1280, 832);
+        assert_eq!(
+            main_monitor(&mostly_laptop, &[EXTERNAL, LAPTOP]),
+            Some(&LAPTOP)
+        );
+        // Off every monitor: the first one.
+        let lost = bounds(-9000, 0, 1280, 832);
+        assert_eq!(main_monitor(&lost, &[EXTERNAL, LAPTOP]), Some(&EXTERNAL));
+        assert_eq!(main_monitor(&lost, &[]), None);
+    }
+
+    #[test]
+    fn centers_on_the_target_monitor() {
+        let on_laptop = WindowBounds {
+            scale_factor: Some(2.0),
+            ..bounds(100, 100, 1280, 832)
+        };
+        // Half the physical size at 1x, centered in the external work area.
+        assert_eq!(
+            center_on(&on_laptop, &EXTERNAL),
+            WindowBounds {
+                scale_factor: Some(1.0),
+                ..bounds(1920 + 1600, 872, 640, 416)
+            }
+        );
+        // Shrunk to fit a smaller monitor.
+        let huge = bounds(0, 0, 3000, 2000);
+        assert_eq!(center_on(&huge, &LAPTOP), bounds(0, 0, 1920, 1080));
+    }
+}
@@ -0,0 +1,29 @@
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::settings;
+
+const DEFAULT_MAX_READ_BYTES: usize = 1024 * 1024;
+
+#[tauri::command]
+pub fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), String> {
+    // Clipboard contents may be sensitive, so only the size is logged.
+    log::debug!("copying {} bytes to the clipboard", text.len());
+    app.clipboard().write_text(text).map_err(|e| e.to_string())
+}
+
+/// Reads text from the clipboard, refusing payloads larger than
+/// `clipboard-max-bytes` (1 MB unless configured).
+#[tauri::command]
+pub fn read_clipboard(app: AppHandle) -> Result<String, String> {
+    let max_bytes = settings::get::<usize>(&app, settings::CLIPBOARD_MAX_BYTES)
+        .unwrap_or(DEFAULT_MAX_READ_BYTES);
+    let text = app.clipboard().read_text().map_err(|e| e.to_string())?;
+    if text.len() > max_bytes {
+        return Err(format!(
+            "clipboard holds {} bytes, more than the {max_bytes} byte limit",
+            text.len()
+        ));
+    }
+    Ok(text)
+}
@@ -1,6 +1,13 @@
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
-use tauri_plugin_shell::ShellExt;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+use url::Url;
+
+/// Schemes the webview may hand to the OS; anything else could reach local
+/// files or arbitrary protocol handlers.
+const EXTERNAL_SCHEMES: &[&str] = &["http", "https", "mailto"];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppInfo {
@@ -16,23 +23,131 @@ pub fn get_app_version() -> AppInfo {
     }
 }
 
+/// Build metadata for About panels and bug reports.
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub tauri_version: &'static str,
+    pub build_date: &'static str,
+    pub git_commit: &'static str,
+    pub target_triple: &'static str,
+}
+
+#[tauri::command]
+pub fn get_build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        tauri_version: tauri::VERSION,
+        build_date: env!("BOLT_BUILD_DATE"),
+        git_commit: env!("BOLT_GIT_COMMIT"),
+        target_triple: env!("BOLT_TARGET_TRIPLE"),
+    }
+}
+
 #[tauri::command]
 pub fn get_app_path(app: AppHandle, key: String) -> Option<String> {
-    app.path()
-        .ok()
-        .and_then(|p| match key.as_str() {
-            "appData" => p.app_data_dir().ok().map(|p| p.to_string_lossy().to_string()),
-            "appCache" => p.app_cache_dir().ok().map(|p| p.to_string_lossy().to_string()),
-            "appLog" => p.app_log_dir().ok().map(|p| p.to_string_lossy().to_string()),
-            "home" => p.home_dir().ok().map(|p| p.to_string_lossy().to_string()),
-            "temp" => p.temp_dir().ok().map(|p| p.to_string_lossy().to_string()),
-            _ => None,
-        })
+    let path = app.path();
+    match key.as_str() {
+        "appData" => path.app_data_dir(),
+        "appCache" => path.app_cache_dir(),
+        "appLog" => path.app_log_dir(),
+        "home" => path.home_dir(),
+        "temp" => path.temp_dir(),
+        _ => return None,
+    }
+    .ok()
+    .map(|p| p.to_string_lossy().to_string())
+}
+
+fn validate_external_url(url: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url).map_err(|e| format!("invalid URL \"{url}\": {e}"))?;
+    if !EXTERNAL_SCHEMES.contains(&parsed.scheme()) {
+        return Err(format!(
+            "refusing to open \"{}:\" URLs; only http, https and mailto are allowed",
+            parsed.scheme()
+        ));
+    }
+    Ok(parsed)
 }
 
 #[tauri::command]
-pub async fn open_external_link(app: AppHandle, url: String) -> Result<(), String> {
-    app.shell()
-        .open(&url, None)
+pub fn open_external(app: AppHandle, url: String) -> Result<(), String> {
+    let url = validate_external_url(&url)?;
+    log::info!("opening external URL: {url}");
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
         .map_err(|e| e.to_string())
 }
+
+/// Directories whose contents may be revealed in the file manager.
+fn revealable_roots(app: &AppHandle) -> Vec<PathBuf> {
+    let path = app.path();
+    [
+        path.app_data_dir(),
+        path.app_log_dir(),
+        path.app_cache_dir(),
+        path.download_dir(),
+    ]
+    .into_iter()
+    .filter_map(Result::ok)
+    .filter_map(|root| root.canonicalize().ok())
+    .collect()
+}
+
+/// Resolves `path` and checks that it exists under one of `roots`.
+fn resolve_within(path: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let resolved = Path::new(path)
+        .canonicalize()
+        .map_err(|e| format!("cannot reveal \"{path}\": {e}"))?;
+    if roots.iter().any(|root| resolved.starts_with(root)) {
+        Ok(resolved)
+    } else {
+        Err(format!("\"{path}\" is outside the app's folders"))
+    }
+}
+
+/// Opens the OS file manager with `path` selected.
+#[tauri::command]
+pub fn reveal_in_folder(app: AppHandle, path: String) -> Result<(), String> {
+    let resolved = resolve_within(&path, &revealable_roots(&app))?;
+    app.opener()
+        .reveal_item_in_dir(resolved)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_web_and_mail_urls() {
+        assert!(validate_external_url("https://bolt.gives/docs").is_ok());
+        assert!(validate_external_url("http://localhost:5173").is_ok());
+        assert!(validate_external_url("mailto:support@bolt.gives").is_ok());
+    }
+
+    #[test]
+    fn rejects_local_and_custom_schemes() {
+        assert!(validate_external_url("file:///etc/passwd").is_err());
+        assert!(validate_external_url("javascript:alert(1)").is_err());
+        assert!(validate_external_url("vscode://open").is_err());
+        assert!(validate_external_url("not a url").is_err());
+    }
+
+    #[test]
+    fn reveals_only_existing_paths_inside_roots() {
+        let root = std::env::temp_dir().join(format!("bolt-gives-reveal-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let file = root.join("crash-1.txt");
+        std::fs::write(&file, "").unwrap();
+        let roots = [root.canonicalize().unwrap()];
+
+        assert!(resolve_within(file.to_str().unwrap(), &roots).is_ok());
+        let escape = root.join("..").join("..");
+        assert!(resolve_within(escape.to_str().unwrap(), &roots).is_err());
+        let missing = root.join("missing.txt");
+        assert!(resolve_within(missing.to_str().unwrap(), &roots).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
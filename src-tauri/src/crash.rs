@@ -0,0 +1,110 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::{main_window, settings};
+
+const CRASH_DETECTED_EVENT: &str = "crash-detected";
+const REPORT_PREFIX: &str = "crash-";
+const MAX_REPORTS: usize = 5;
+
+/// Where the panic hook writes reports; unset until `setup` has resolved the
+/// log directory, so very early panics only reach stderr.
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Reports from earlier runs that the frontend hasn't been told about yet.
+#[derive(Default)]
+pub struct CrashReports(Mutex<Vec<String>>);
+
+/// Installs a panic hook that writes a crash report before the default hook runs.
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(dir) = CRASH_DIR.get() {
+            let _ = write_report(dir, &info.to_string());
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_report(dir: &Path, panic: &str) -> io::Result<()> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let thread = std::thread::current();
+    let report = format!(
+        "bolt.gives {version} crashed\n\
+         os: {os} ({arch})\n\
+         thread: {thread}\n\
+         time: {millis} ms since the Unix epoch\n\n\
+         {panic}\n\n\
+         backtrace:\n{backtrace}\n",
+        version = env!("CARGO_PKG_VERSION"),
+        os = std::env::consts::OS,
+        arch = std::env::consts::ARCH,
+        thread = thread.name().unwrap_or("<unnamed>"),
+        backtrace = Backtrace::force_capture(),
+    );
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(format!("{REPORT_PREFIX}{millis}.txt")), report)
+}
+
+/// Crash report file names, oldest first.
+fn reports(dir: &Path) -> io::Result<Vec<String>> {
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(REPORT_PREFIX) && name.ends_with(".txt"))
+        .collect();
+    // Timestamps have the same number of digits, so names sort chronologically.
+    names.sort();
+    Ok(names)
+}
+
+/// Points the panic hook at the log directory, prunes old reports and queues
+/// any written since the last launch for the frontend.
+pub fn init(app: &AppHandle) {
+    let Ok(dir) = app.path().app_log_dir() else {
+        return;
+    };
+    let _ = CRASH_DIR.set(dir.clone());
+
+    let Ok(mut names) = reports(&dir) else {
+        return;
+    };
+    let excess = names.len().saturating_sub(MAX_REPORTS);
+    for name in names.drain(..excess) {
+        let _ = fs::remove_file(dir.join(name));
+    }
+
+    let last_seen = settings::get::<String>(app, settings::LAST_SEEN_CRASH_REPORT);
+    let new: Vec<String> = names
+        .iter()
+        .filter(|name| last_seen.as_ref().map_or(true, |seen| *name > seen))
+        .map(|name| dir.join(name).to_string_lossy().into_owned())
+        .collect();
+    if let Some(newest) = names.last() {
+        let _ = settings::set(app, settings::LAST_SEEN_CRASH_REPORT, newest);
+    }
+    if !new.is_empty() {
+        log::warn!("found {} crash report(s) from a previous run", new.len());
+        *app.state::<CrashReports>().0.lock().unwrap() = new;
+    }
+}
+
+/// Hands queued crash report paths to the main window once it has loaded.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let app = webview.app_handle();
+    let paths = std::mem::take(&mut *app.state::<CrashReports>().0.lock().unwrap());
+    if !paths.is_empty() {
+        let _ = app.emit_to(main_window::LABEL, CRASH_DETECTED_EVENT, paths);
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Webview};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::main_window;
+
+const SCHEME: &str = "bolt";
+const DEEP_LINK_EVENT: &str = "deep-link";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeepLink {
+    pub url: String,
+    pub path: String,
+    pub query: BTreeMap<String, String>,
+}
+
+/// Splits a `bolt://` URL into an app path and its query parameters.
+///
+/// The URL host is treated as the first path segment, so
+/// `bolt://project/123?tab=files` becomes path `project/123`.
+pub fn parse(url: &Url) -> Option<DeepLink> {
+    if url.scheme() != SCHEME {
+        return None;
+    }
+    let host = url.host_str().unwrap_or_default();
+    let path = format!("{host}{}", url.path())
+        .trim_matches('/')
+        .to_string();
+    Some(DeepLink {
+        url: url.to_string(),
+        path,
+        query: url.query_pairs().into_owned().collect(),
+    })
+}
+
+/// Holds links that arrive before the frontend has loaded; `None` once it is ready.
+pub struct DeepLinkQueue(Mutex<Option<Vec<DeepLink>>>);
+
+impl Default for DeepLinkQueue {
+    fn default() -> Self {
+        Self(Mutex::new(Some(Vec::new())))
+    }
+}
+
+/// Subscribes to incoming links and queues the one the app was launched with.
+pub fn init(app: &AppHandle) {
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("failed to register the {SCHEME}:// scheme: {e}");
+    }
+
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        for url in urls {
+            handle(app, &url);
+        }
+    }
+
+    let handle_app = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle(&handle_app, &url);
+        }
+    });
+}
+
+fn handle(app: &AppHandle, url: &Url) {
+    let Some(link) = parse(url) else {
+        log::warn!("ignoring deep link with unexpected scheme: {url}");
+        return;
+    };
+
+    main_window::show(app);
+    let queue = app.state::<DeepLinkQueue>();
+    let mut pending = queue.0.lock().unwrap();
+    match pending.as_mut() {
+        Some(links) => links.push(link),
+        None => {
+            let _ = app.emit_to(main_window::LABEL, DEEP_LINK_EVENT, link);
+        }
+    }
+}
+
+/// Delivers queued links once the main window has loaded.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let app = webview.app_handle();
+    let links = app.state::<DeepLinkQueue>().0.lock().unwrap().take();
+    for link in links.unwrap_or_default() {
+        let _ = app.emit_to(main_window::LABEL, DEEP_LINK_EVENT, link);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_path_and_query() {
+        let url = Url::parse("bolt://project/123?tab=files").unwrap();
+        let link = parse(&url).unwrap();
+        assert_eq!(link.path, "project/123");
+        assert_eq!(
+            link.query,
+            BTreeMap::from([("tab".to_string(), "files".to_string())])
+        );
+    }
+
+    #[test]
+    fn parses_link_without_path() {
+        let link = parse(&Url::parse("bolt://settings").unwrap()).unwrap();
+        assert_eq!(link.path, "settings");
+        assert!(link.query.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        assert_eq!(
+            parse(&Url::parse("https://bolt.gives/project").unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use tauri::{AppHandle, Manager, WebviewWindow};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
+
+use crate::error::AppError;
+use crate::{main_window, settings};
+
+/// Devtools and other escape hatches are always available in debug builds;
+/// release builds need the hidden `devtools-enabled` setting, which support
+/// can have a user turn on from the Help menu.
+pub fn ensure_enabled(app: &AppHandle) -> Result<(), AppError> {
+    let enabled = settings::get::<bool>(app, settings::DEVTOOLS_ENABLED).unwrap_or(false);
+    if !cfg!(debug_assertions) && !enabled {
+        return Err(AppError::Forbidden(
+            "devtools are disabled in this build".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Turns the `devtools-enabled` gate on or off after asking. Only reachable
+/// from the native menu, so the webview can't unlock devtools for itself.
+pub fn toggle_access(app: &AppHandle) {
+    let enabled = settings::get::<bool>(app, settings::DEVTOOLS_ENABLED).unwrap_or(false);
+    let (title, message, action) = if enabled {
+        (
+            "Disable developer tools",
+            "Turn off developer tools for bolt.gives?",
+            "Disable",
+        )
+    } else {
+        (
+            "Enable developer tools",
+            "Developer tools give full access to the app's data. Only turn them on if support asked you to.",
+            "Enable",
+        )
+    };
+    let handle = app.clone();
+    app.dialog()
+        .message(message)
+        .title(title)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            action.to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            if !confirmed {
+                return;
+            }
+            let result = if enabled {
+                settings::clear(&handle, settings::DEVTOOLS_ENABLED)
+            } else {
+                settings::update(&handle, settings::DEVTOOLS_ENABLED, true)
+            };
+            match result {
+                Ok(()) => log::info!(
+                    "devtools access turned {}",
+                    if enabled { "off" } else { "on" }
+                ),
+                Err(e) => log::warn!("failed to change devtools access: {e}"),
+            }
+        });
+}
+
+fn main_webview(app: &AppHandle) -> Result<WebviewWindow, AppError> {
+    ensure_enabled(app)?;
+    app.get_webview_window(main_window::LABEL)
+        .ok_or_else(|| AppError::Window("the main window is not open".to_string()))
+}
+
+#[tauri::command]
+pub fn open_devtools(app: AppHandle) -> Result<(), AppError> {
+    main_webview(&app)?.open_devtools();
+    log::info!("devtools opened");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn close_devtools(app: AppHandle) -> Result<(), AppError> {
+    main_webview(&app)?.close_devtools();
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::bounds::{self, MonitorInfo};
+use crate::commands::{self, BuildInfo};
+use crate::error::AppError;
+use crate::system::{self, SystemInfo};
+use crate::{clipboard, logs, settings};
+
+/// Log lines included in a report.
+const LOG_LINES: usize = 200;
+
+#[derive(Debug, Serialize)]
+struct Diagnostics {
+    build: BuildInfo,
+    system: SystemInfo,
+    monitors: Vec<MonitorInfo>,
+    settings: Map<String, Value>,
+    logs: Vec<String>,
+}
+
+/// Bundles build info, the system info, monitors, the settings and the
+/// recent log lines into a JSON report for bug reports, copying it to
+/// the clipboard when `copy` is set.
+///
+/// Secrets, proxy credentials, the install id and the hostname are masked,
+/// since reports get shared. Parts that can't be read are left empty rather
+/// than failing the report.
+#[tauri::command]
+pub async fn collect_diagnostics(app: AppHandle, copy: Option<bool>) -> Result<String, AppError> {
+    // Reading the system info takes a while, so keep it off the async runtime.
+    let mut system = tauri::async_runtime::spawn_blocking(system::collect)
+        .await
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    if system.hostname.is_some() {
+        system.hostname = Some("[redacted]".to_string());
+    }
+    let diagnostics = Diagnostics {
+        build: commands::get_build_info(),
+        system,
+        monitors: bounds::list_monitors(app.clone()).unwrap_or_default(),
+        settings: settings::report_snapshot(&app),
+        logs: logs::get_recent_logs(app.clone(), LOG_LINES).unwrap_or_default(),
+    };
+    // Only strings, numbers and maps, so this can't fail.
+    let report = serde_json::to_string_pretty(&diagnostics).expect("diagnostics serialize to JSON");
+    if copy.unwrap_or(false) {
+        clipboard::copy_to_clipboard(app, report.clone()).map_err(AppError::Io)?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,63 @@
+use std::io;
+use std::path::Path;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Bytes available to the current user on the volume holding `path`.
+///
+/// `path` doesn't have to exist yet (the data dir on a fresh install); its
+/// closest existing ancestor is measured instead.
+pub fn free_space(path: &Path) -> io::Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|dir| dir.exists())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no existing parent directory"))?;
+    fs4::available_space(existing)
+}
+
+/// Human-readable size for messages, e.g. `1.5 GB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["bytes", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} bytes")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Free space on the volume holding the app data directory, in bytes.
+#[tauri::command]
+pub fn get_free_disk_space(app: AppHandle) -> Result<u64, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(free_space(&dir)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_sizes() {
+        assert_eq!(format_size(512), "512 bytes");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(250 * 1024 * 1024), "250.0 MB");
+        assert_eq!(format_size(3 << 40), "3.0 TB");
+    }
+
+    #[test]
+    fn measures_missing_paths_through_their_parent() {
+        let missing = std::env::temp_dir().join("bolt-gives-missing/nested");
+        assert!(free_space(&missing).is_ok());
+    }
+}
@@ -0,0 +1,643 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_http::reqwest::{header, Response, StatusCode};
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::error::AppError;
+use crate::{http, main_window, progress};
+
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+const PROGRESS_EVENT: &str = "download://progress";
+const CANCELLED_EVENT: &str = "download-cancelled";
+
+/// The downloads running right now, [`download_file`] calls and update
+/// downloads alike, with the token that cancels each.
+#[derive(Default)]
+pub struct ActiveDownloads {
+    next_id: AtomicU64,
+    tokens: Mutex<HashMap<u64, CancellationToken>>,
+}
+
+/// A running download's entry in [`ActiveDownloads`], removed when dropped.
+pub struct ActiveDownload {
+    app: AppHandle,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl ActiveDownload {
+    /// Resolves once [`cancel_downloads`] has been called.
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+}
+
+impl Drop for ActiveDownload {
+    fn drop(&mut self) {
+        let downloads = self.app.state::<ActiveDownloads>();
+        downloads.tokens.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Registers a download so [`cancel_downloads`] can stop it.
+pub fn begin(app: &AppHandle) -> ActiveDownload {
+    let downloads = app.state::<ActiveDownloads>();
+    let id = downloads.next_id.fetch_add(1, Ordering::SeqCst);
+    let token = CancellationToken::new();
+    downloads.tokens.lock().unwrap().insert(id, token.clone());
+    ActiveDownload {
+        app: app.clone(),
+        id,
+        token,
+    }
+}
+
+/// Whether a download is running, which an unattended restart must wait for.
+pub fn in_progress(app: &AppHandle) -> bool {
+    !app.state::<ActiveDownloads>()
+        .tokens
+        .lock()
+        .unwrap()
+        .is_empty()
+}
+
+/// Payload of `download-cancelled`; `dest` is `None` for an update download.
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadCancelled {
+    pub dest: Option<String>,
+}
+
+/// Tells the main window a download stopped because it was cancelled.
+pub fn report_cancelled(app: &AppHandle, dest: Option<&str>) {
+    log::info!("download of {} cancelled", dest.unwrap_or("the update"));
+    let _ = app.emit_to(
+        main_window::LABEL,
+        CANCELLED_EVENT,
+        DownloadCancelled {
+            dest: dest.map(str::to_string),
+        },
+    );
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub dest: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+/// What an interrupted download was fetching, kept next to its `.part` file so
+/// a retry can tell whether the bytes on disk can be resumed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PartialDownload {
+    url: String,
+    etag: Option<String>,
+    total: Option<u64>,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path.as_os_str());
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    with_suffix(dest, ".part")
+}
+
+fn meta_path(dest: &Path) -> PathBuf {
+    with_suffix(dest, ".part.json")
+}
+
+fn read_meta(dest: &Path) -> Option<PartialDownload> {
+    serde_json::from_slice(&fs::read(meta_path(dest)).ok()?).ok()
+}
+
+/// Removes the partial file and its metadata.
+fn discard(dest: &Path) {
+    let _ = fs::remove_file(partial_path(dest));
+    let _ = fs::remove_file(meta_path(dest));
+}
+
+/// Bytes of `dest` already on disk from an interrupted download of `url`, or
+/// `0` when there is nothing usable to resume.
+fn resume_offset(dest: &Path, url: &Url) -> (u64, Option<PartialDownload>) {
+    let meta = read_meta(dest).filter(|meta| meta.url == url.as_str());
+    let len = fs::metadata(partial_path(dest)).map_or(0, |metadata| metadata.len());
+    match meta {
+        Some(meta) if len > 0 && meta.total.map_or(true, |total| len < total) => (len, Some(meta)),
+        _ => (0, None),
+    }
+}
+
+/// Parses a `Content-Range: bytes <start>-<end>/<total>` header; the total
+/// may be `*` when the server doesn't know it.
+fn parse_content_range(value: &str) -> Option<(u64, u64, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let (start, end) = span.split_once('-')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start.parse().ok()?, end.parse().ok()?, total))
+}
+
+/// Writes a download to `<dest>.part` while hashing it, and only moves it to
+/// `dest` once the hash checks out.
+struct VerifiedFile {
+    dest: PathBuf,
+    file: File,
+    hasher: Sha256,
+}
+
+impl VerifiedFile {
+    /// Starts a new partial file, replacing any left over.
+    fn create(dest: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(partial_path(dest))?,
+            dest: dest.to_path_buf(),
+            hasher: Sha256::new(),
+        })
+    }
+
+    /// Continues the partial file, hashing what it already holds.
+    fn resume(dest: &Path) -> io::Result<Self> {
+        let partial = partial_path(dest);
+        let mut hasher = Sha256::new();
+        let mut existing = File::open(&partial)?;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let read = existing.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+        Ok(Self {
+            file: OpenOptions::new().append(true).open(&partial)?,
+            dest: dest.to_path_buf(),
+            hasher,
+        })
+    }
+
+    fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.hasher.update(chunk);
+        self.file.write_all(chunk)
+    }
+
+    /// Checks the hash against `expected_sha256`, if given, and moves the file
+    /// into place. A mismatch removes the partial file, since resuming it
+    /// would only reproduce the same bytes.
+    fn finish(self, expected_sha256: Option<&str>) -> Result<(), AppError> {
+        self.file.sync_all()?;
+        drop(self.file);
+        let actual: String = self
+            .hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        if let Some(expected) = expected_sha256 {
+            if !actual.eq_ignore_ascii_case(expected.trim()) {
+                discard(&self.dest);
+                return Err(AppError::Integrity(format!(
+                    "{} has SHA-256 {actual}, expected {expected}",
+                    self.dest.display()
+                )));
+            }
+        }
+        fs::rename(partial_path(&self.dest), &self.dest)?;
+        let _ = fs::remove_file(meta_path(&self.dest));
+        Ok(())
+    }
+}
+
+/// Folders downloads may be saved in, with symlinks resolved.
+pub struct DestRoots {
+    /// Only its subfolders: the files at the top are the app's own store,
+    /// backups and markers.
+    app_data: Option<PathBuf>,
+    downloads: Option<PathBuf>,
+    /// Never written to, even where it lies inside `app_data`.
+    logs: Option<PathBuf>,
+}
+
+pub fn allowed_roots(app: &AppHandle) -> DestRoots {
+    let path = app.path();
+    let resolve = |dir: tauri::Result<PathBuf>| dir.ok()?.canonicalize().ok();
+    DestRoots {
+        app_data: resolve(path.app_data_dir()),
+        downloads: resolve(path.download_dir()),
+        logs: resolve(path.app_log_dir()),
+    }
+}
+
+/// `path` with its symlinks resolved, for a file that may not exist yet: the
+/// deepest ancestor that exists is canonicalized and the rest appended.
+/// Fails on a dangling symlink, which writing would follow somewhere unknown.
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match existing.canonicalize() {
+            Ok(resolved) => {
+                return Ok(missing
+                    .iter()
+                    .rev()
+                    .fold(resolved, |resolved, name| resolved.join(name)))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if fs::symlink_metadata(existing).is_ok() {
+                    return Err(io::Error::other("the path is a dangling symlink"));
+                }
+                missing.push(existing.file_name().ok_or(e)?);
+                existing = existing
+                    .parent()
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether the resolved path `dest` is in a subfolder of the app data
+/// directory or anywhere in the downloads directory, and not in the logs.
+fn is_allowed_dest(dest: &Path, roots: &DestRoots) -> bool {
+    let in_app_data = roots.app_data.as_ref().is_some_and(|root| {
+        dest.strip_prefix(root)
+            .is_ok_and(|rest| rest.components().count() > 1)
+    });
+    let in_downloads = roots
+        .downloads
+        .as_ref()
+        .is_some_and(|root| dest.starts_with(root) && dest != root);
+    let in_logs = roots
+        .logs
+        .as_ref()
+        .is_some_and(|root| dest.starts_with(root));
+    (in_app_data || in_downloads) && !in_logs
+}
+
+/// Resolves `dest` through any symlinks and returns it if it may be written
+/// to; see [`is_allowed_dest`]. Only absolute paths without `..` are
+/// considered.
+pub fn resolve_dest(dest: &Path, roots: &DestRoots) -> Option<PathBuf> {
+    if !dest.is_absolute()
+        || dest
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+    {
+        return None;
+    }
+    resolve(dest)
+        .ok()
+        .filter(|resolved| is_allowed_dest(resolved, roots))
+}
+
+fn etag(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(header::ETAG)?
+        .to_str()
+        .ok()
+        .map(str::to_string)
+}
+
+/// Whether a response's `ETag` shows it is the entity the partial file was
+/// fetched from. A partial file with an `ETag` only continues a response that
+/// has the same one.
+fn same_etag(expected: Option<&str>, actual: Option<&str>) -> bool {
+    match (expected, actual) {
+        (Some(expected), actual) => actual == Some(expected),
+        (None, _) => true,
+    }
+}
+
+/// Whether a `206` answer continues `partial` from `offset`: same entity and
+/// same size, starting where the file on disk ends.
+fn continues(response: &Response, offset: u64, partial: &PartialDownload) -> bool {
+    let Some((start, _, total)) = response
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+    else {
+        return false;
+    };
+    start == offset
+        && same_etag(partial.etag.as_deref(), etag(response).as_deref())
+        && (partial.total.is_none() || total == partial.total)
+}
+
+/// Requests `url`, resuming the partial download at `dest` if the server
+/// supports ranges and the file hasn't changed, and returns the response with
+/// the file to write into and the offset it continues from.
+async fn start(
+    app: &AppHandle,
+    url: &Url,
+    dest: &Path,
+) -> Result<(Response, VerifiedFile, u64), AppError> {
+    let client = http::client(app, DOWNLOAD_TIMEOUT).map_err(AppError::Network)?;
+    let (offset, partial) = resume_offset(dest, url);
+    if let Some(partial) = partial {
+        let mut request = client
+            .get(url.clone())
+            .header(header::RANGE, format!("bytes={offset}-"));
+        // Servers answer with the whole file instead if it changed.
+        if let Some(etag) = &partial.etag {
+            request = request.header(header::IF_RANGE, etag);
+        }
+        let response = request.send().await?;
+        if response.status() == StatusCode::PARTIAL_CONTENT
+            && continues(&response, offset, &partial)
+        {
+            log::info!("resuming download of {url} at byte {offset}");
+            return Ok((response, VerifiedFile::resume(dest)?, offset));
+        }
+        if response.status().is_success() && response.status() != StatusCode::PARTIAL_CONTENT {
+            log::info!("{url} can't be resumed, downloading it again");
+            return Ok((response, VerifiedFile::create(dest)?, 0));
+        }
+        log::info!(
+            "{url} answered the range request with {}, downloading it again",
+            response.status()
+        );
+    }
+    discard(dest);
+    let response = client.get(url.clone()).send().await?.error_for_status()?;
+    Ok((response, VerifiedFile::create(dest)?, 0))
+}
+
+/// Downloads `url` over HTTPS to `dest`, which must be in a folder inside the
+/// app data directory or in the downloads directory, reporting progress
+/// through `download://progress`.
+///
+/// An interrupted download leaves `<dest>.part` behind and the next call for
+/// the same URL picks up where it stopped, as long as the server supports
+/// range requests and the file (by `ETag` and size) hasn't changed. With
+/// `expected_sha256`, the file is only kept if its SHA-256 matches.
+/// [`cancel_downloads`] stops it and removes the partial file.
+#[tauri::command]
+pub async fn download_file(
+    app: AppHandle,
+    url: String,
+    dest: String,
+    expected_sha256: Option<String>,
+) -> Result<(), AppError> {
+    let url = Url::parse(&url)
+        .map_err(|e| AppError::InvalidInput(format!("invalid URL \"{url}\": {e}")))?;
+    if url.scheme() != "https" {
+        return Err(AppError::InvalidInput(format!(
+            "only https URLs can be downloaded, not \"{url}\""
+        )));
+    }
+    let Some(dest_path) = resolve_dest(Path::new(&dest), &allowed_roots(&app)) else {
+        return Err(AppError::Forbidden(format!(
+            "downloads can only be saved in a folder in the app data directory or in the downloads directory, not \"{dest}\""
+        )));
+    };
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let download = begin(&app);
+    let result = tokio::select! {
+        result = transfer(&app, &url, &dest, &dest_path, expected_sha256.as_deref()) => result,
+        () = download.cancelled() => {
+            discard(&dest_path);
+            report_cancelled(&app, Some(&dest));
+            Err(AppError::Cancelled(format!("the download of {url} was cancelled")))
+        }
+    };
+    drop(download);
+    progress::clear(&app);
+    result
+}
+
+/// Stops every running download, update downloads included, and returns how
+/// many there were. Each removes its partial files and sends
+/// `download-cancelled` once it has stopped; an update that is already
+/// installing runs to completion.
+#[tauri::command]
+pub fn cancel_downloads(app: AppHandle) -> usize {
+    let tokens = app.state::<ActiveDownloads>();
+    let tokens = tokens.tokens.lock().unwrap();
+    for token in tokens.values() {
+        token.cancel();
+    }
+    tokens.len()
+}
+
+/// Fetches `url` into `dest_path`, resuming a partial download if possible.
+async fn transfer(
+    app: &AppHandle,
+    url: &Url,
+    dest: &str,
+    dest_path: &Path,
+    expected_sha256: Option<&str>,
+) -> Result<(), AppError> {
+    let (mut response, mut file, offset) = start(app, url, dest_path).await?;
+    let total = response.content_length().map(|len| len + offset);
+    let meta = PartialDownload {
+        url: url.to_string(),
+        etag: etag(&response),
+        total,
+    };
+    fs::write(
+        meta_path(dest_path),
+        serde_json::to_vec(&meta).map_err(|e| AppError::Io(e.to_string()))?,
+    )?;
+
+    let mut downloaded = offset;
+    while let Some(chunk) = response.chunk().await? {
+        file.write(&chunk)?;
+        downloaded += chunk.len() as u64;
+        let percent = total
+            .filter(|total| *total > 0)
+            .map(|total| (downloaded as f64 / total as f64 * 100.0).min(100.0));
+        progress::report(app, percent);
+        let _ = app.emit_to(
+            main_window::LABEL,
+            PROGRESS_EVENT,
+            DownloadProgress {
+                dest: dest.to_string(),
+                downloaded,
+                total,
+                percent,
+            },
+        );
+    }
+    file.finish(expected_sha256)?;
+    log::info!("downloaded {url} to {dest}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SHA-256 of "hello world".
+    const HELLO_SHA256: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("bolt-gives-download-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn download(dest: &Path, expected_sha256: Option<&str>) -> Result<(), AppError> {
+        let mut file = VerifiedFile::create(dest).unwrap();
+        file.write(b"hello ").unwrap();
+        file.write(b"world").unwrap();
+        file.finish(expected_sha256)
+    }
+
+    #[test]
+    fn keeps_files_with_matching_checksums() {
+        let dir = temp_dir("match");
+        let dest = dir.join("file.txt");
+        download(&dest, Some(&HELLO_SHA256.to_uppercase())).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"hello world");
+        assert!(!dir.join("file.txt.part").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn removes_the_partial_file_on_checksum_mismatch() {
+        let dir = temp_dir("mismatch");
+        let dest = dir.join("file.txt");
+        let error = download(&dest, Some(&"0".repeat(64))).unwrap_err();
+        assert_eq!(error.code(), "integrity");
+        assert!(!dest.exists());
+        assert!(!dir.join("file.txt.part").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resumed_files_hash_the_bytes_already_on_disk() {
+        let dir = temp_dir("resume");
+        let dest = dir.join("file.txt");
+        fs::write(partial_path(&dest), "hello ").unwrap();
+        let mut file = VerifiedFile::resume(&dest).unwrap();
+        file.write(b"world").unwrap();
+        file.finish(Some(HELLO_SHA256)).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"hello world");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resumes_only_partial_downloads_of_the_same_url() {
+        let dir = temp_dir("offset");
+        let dest = dir.join("file.bin");
+        let url = Url::parse("https://example.com/file.bin").unwrap();
+        assert_eq!(resume_offset(&dest, &url), (0, None));
+
+        let meta = PartialDownload {
+            url: url.to_string(),
+            etag: Some("\"v1\"".to_string()),
+            total: Some(10),
+        };
+        fs::write(meta_path(&dest), serde_json::to_vec(&meta).unwrap()).unwrap();
+        fs::write(partial_path(&dest), "1234").unwrap();
+        assert_eq!(resume_offset(&dest, &url), (4, Some(meta)));
+
+        let other = Url::parse("https://example.com/other.bin").unwrap();
+        assert_eq!(resume_offset(&dest, &other), (0, None));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn resumes_only_the_same_etag() {
+        assert!(same_etag(Some("\"v1\""), Some("\"v1\"")));
+        assert!(!same_etag(Some("\"v1\""), Some("\"v2\"")));
+        assert!(!same_etag(Some("\"v1\""), None));
+        assert!(same_etag(None, Some("\"v1\"")));
+        assert!(same_etag(None, None));
+    }
+
+    #[test]
+    fn parses_content_ranges() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/200"),
+            Some((100, 199, Some(200)))
+        );
+        assert_eq!(parse_content_range("bytes 0-9/*"), Some((0, 9, None)));
+        assert_eq!(parse_content_range("bytes */200"), None);
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+
+    #[test]
+    fn restricts_destinations_to_the_allowed_roots() {
+        let roots = DestRoots {
+            app_data: Some(PathBuf::from("/data/bolt")),
+            downloads: Some(PathBuf::from("/home/u/Downloads")),
+            logs: Some(PathBuf::from("/data/bolt/logs")),
+        };
+        assert!(is_allowed_dest(
+            Path::new("/data/bolt/models/a.bin"),
+            &roots
+        ));
+        assert!(is_allowed_dest(
+            Path::new("/home/u/Downloads/a.zip"),
+            &roots
+        ));
+        assert!(!is_allowed_dest(Path::new("/data/bolt"), &roots));
+        assert!(!is_allowed_dest(
+            Path::new("/data/bolt/app-data.json"),
+            &roots
+        ));
+        assert!(!is_allowed_dest(
+            Path::new("/data/bolt/logs/bolt-gives.log"),
+            &roots
+        ));
+        assert!(!is_allowed_dest(
+            Path::new("/data/bolt-other/a.bin"),
+            &roots
+        ));
+        assert!(resolve_dest(Path::new("/data/bolt/../etc/passwd"), &roots).is_none());
+        assert!(resolve_dest(Path::new("models/a.bin"), &roots).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_symlinks_out_of_the_roots() {
+        let dir = temp_dir("symlink");
+        let root = dir.join("root");
+        let outside = dir.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.join("gone"), root.join("dangling")).unwrap();
+
+        let resolved = resolve(&root.join("link/sub/a.bin")).unwrap();
+        assert_eq!(
+            resolved,
+            outside.canonicalize().unwrap().join("sub").join("a.bin")
+        );
+        assert!(resolve(&root.join("dangling")).is_err());
+
+        let roots = DestRoots {
+            app_data: None,
+            downloads: Some(root.canonicalize().unwrap()),
+            logs: None,
+        };
+        assert!(resolve_dest(&root.join("link/a.bin"), &roots).is_none());
+        assert!(resolve_dest(&root.join("a.bin"), &roots).is_some());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
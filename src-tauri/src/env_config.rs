@@ -0,0 +1,155 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{logs, settings, updater};
+
+/// Environment used when `BOLT_ENV` isn't set.
+const DEFAULT_ENV: &str = "prod";
+
+/// What a `config.<env>.json` may contain.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct EnvFile {
+    update_endpoints: Option<Vec<String>>,
+    log_level: Option<String>,
+    /// Generic settings by key, e.g. `{"auto-lock-blur": true}`.
+    #[serde(default)]
+    features: Map<String, Value>,
+}
+
+/// Defaults for one deployment environment, read from `config.<env>.json`
+/// next to the executable. They apply wherever the store has no value, so a
+/// setting the user chose always wins.
+#[derive(Debug, Default)]
+pub struct EnvConfig {
+    values: Map<String, Value>,
+}
+
+/// `config.<env>.json`, for environment names that can't reach outside the
+/// executable's folder.
+fn file_name(env: &str) -> Option<String> {
+    let valid = !env.is_empty()
+        && env
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    valid.then(|| format!("config.{env}.json"))
+}
+
+/// Validates an environment config and turns it into defaults keyed by
+/// setting.
+fn parse(json: &str) -> Result<Map<String, Value>, String> {
+    let file: EnvFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let mut values = Map::new();
+    if let Some(endpoints) = file.update_endpoints {
+        updater::validate_endpoints(&endpoints).map_err(|e| e.to_string())?;
+        values.insert(settings::UPDATE_ENDPOINTS.to_string(), endpoints.into());
+    }
+    if let Some(level) = file.log_level {
+        if logs::parse_level(&level).is_none() {
+            return Err(format!("unknown log level \"{level}\""));
+        }
+        values.insert(settings::LOG_LEVEL.to_string(), level.into());
+    }
+    for (key, value) in file.features {
+        settings::validate_generic(&key, &value).map_err(|e| e.to_string())?;
+        if !value.is_null() {
+            values.insert(key, value);
+        }
+    }
+    Ok(values)
+}
+
+/// Reads the config for the environment in `BOLT_ENV` (`prod` if unset). A
+/// missing file means no defaults; an invalid one is logged and ignored.
+///
+/// Runs once the logger is up, so the config can't change `log-max-size-mb`
+/// for the first launch's rotation.
+pub fn load_env_config(app: &AppHandle) -> EnvConfig {
+    let env = std::env::var("BOLT_ENV").unwrap_or_else(|_| DEFAULT_ENV.to_string());
+    let Some(name) = file_name(&env) else {
+        log::error!("ignoring BOLT_ENV \"{env}\": not a valid environment name");
+        return EnvConfig::default();
+    };
+    // Next to the AppImage itself rather than inside its mount.
+    let Some(path) = tauri::process::current_binary(&app.env())
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(name)))
+    else {
+        return EnvConfig::default();
+    };
+
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            log::debug!("no {env} config at {}", path.display());
+            return EnvConfig::default();
+        }
+        Err(e) => {
+            log::error!("failed to read {}: {e}", path.display());
+            return EnvConfig::default();
+        }
+    };
+    match parse(&json) {
+        Ok(values) => {
+            log::info!(
+                "loaded the {env} config from {} ({} settings)",
+                path.display(),
+                values.len()
+            );
+            EnvConfig { values }
+        }
+        Err(e) => {
+            log::error!("ignoring {}: {e}", path.display());
+            EnvConfig::default()
+        }
+    }
+}
+
+/// Every default the environment config sets, by key.
+pub fn defaults(app: &AppHandle) -> Map<String, Value> {
+    app.try_state::<EnvConfig>()
+        .map(|config| config.values.clone())
+        .unwrap_or_default()
+}
+
+/// The environment's default for `key`, if it sets one.
+pub fn value(app: &AppHandle, key: &str) -> Option<Value> {
+    app.try_state::<EnvConfig>()?.values.get(key).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_env_config_into_setting_defaults() {
+        let values = parse(
+            r#"{
+                "update-endpoints": ["https://staging.corp/latest.json"],
+                "log-level": "debug",
+                "features": {"auto-lock-blur": true}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(values[settings::LOG_LEVEL], "debug");
+        assert_eq!(values[settings::AUTO_LOCK_BLUR], true);
+        assert_eq!(values.len(), 3);
+
+        assert!(parse(r#"{"log-level": "loud"}"#).is_err());
+        assert!(parse(r#"{"update-endpoints": ["http://staging.corp"]}"#).is_err());
+        assert!(parse(r#"{"features": {"auth-token": "token"}}"#).is_err());
+        assert!(parse(r#"{"features": {"devtools-enabled": true}}"#).is_err());
+        assert!(parse(r#"{"theme": "dark"}"#).is_err());
+    }
+
+    #[test]
+    fn only_accepts_plain_environment_names() {
+        assert_eq!(file_name("staging").as_deref(), Some("config.staging.json"));
+        assert_eq!(file_name(""), None);
+        assert_eq!(file_name("../prod"), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use tauri_plugin_http::reqwest;
+use tauri_plugin_updater::Error as UpdaterError;
+
+/// Error returned by commands, serialized as `{ code, message }` so the
+/// frontend can branch on `code` instead of parsing messages.
+#[derive(Debug)]
+pub enum AppError {
+    Updater(String),
+    Store(String),
+    Io(String),
+    Network(String),
+    InvalidInput(String),
+    InsufficientSpace(String),
+    Window(String),
+    Forbidden(String),
+    /// Downloaded data didn't match its expected checksum.
+    Integrity(String),
+    /// Stopped on request, e.g. by `cancel_downloads`.
+    Cancelled(String),
+    /// Too many requests; the caller may retry after `retry_after_ms`.
+    RateLimited {
+        retry_after_ms: u64,
+    },
+}
+
+impl AppError {
+    /// Stable, machine-readable category.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Updater(_) => "updater",
+            Self::Store(_) => "store",
+            Self::Io(_) => "io",
+            Self::Network(_) => "network",
+            Self::InvalidInput(_) => "invalid_input",
+            Self::InsufficientSpace(_) => "insufficient_space",
+            Self::Window(_) => "window",
+            Self::Forbidden(_) => "forbidden",
+            Self::Integrity(_) => "integrity",
+            Self::Cancelled(_) => "cancelled",
+            Self::RateLimited { .. } => "rate_limited",
+        }
+    }
+
+    fn message(&self) -> Cow<'_, str> {
+        match self {
+            Self::Updater(message)
+            | Self::Store(message)
+            | Self::Io(message)
+            | Self::Network(message)
+            | Self::InvalidInput(message)
+            | Self::InsufficientSpace(message)
+            | Self::Window(message)
+            | Self::Forbidden(message)
+            | Self::Integrity(message)
+            | Self::Cancelled(message) => Cow::Borrowed(message),
+            Self::RateLimited { retry_after_ms } => {
+                Cow::Owned(format!("too many requests, retry in {retry_after_ms} ms"))
+            }
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let retry_after_ms = match self {
+            Self::RateLimited { retry_after_ms } => Some(*retry_after_ms),
+            _ => None,
+        };
+        let len = if retry_after_ms.is_some() { 3 } else { 2 };
+        let mut error = serializer.serialize_struct("AppError", len)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.message())?;
+        if let Some(retry_after_ms) = retry_after_ms {
+            error.serialize_field("retry_after_ms", &retry_after_ms)?;
+        }
+        error.end()
+    }
+}
+
+impl From<UpdaterError> for AppError {
+    fn from(error: UpdaterError) -> Self {
+        match error {
+            UpdaterError::Reqwest(_) | UpdaterError::Network(_) => Self::Network(error.to_string()),
+            UpdaterError::Io(_) => Self::Io(error.to_string()),
+            _ => Self::Updater(error.to_string()),
+        }
+    }
+}
+
+impl From<tauri_plugin_store::Error> for AppError {
+    fn from(error: tauri_plugin_store::Error) -> Self {
+        Self::Store(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Network(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_message() {
+        let error = AppError::InvalidInput("bad version".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({"code": "invalid_input", "message": "bad version"})
+        );
+    }
+
+    #[test]
+    fn serializes_retry_delay_when_rate_limited() {
+        let error = AppError::RateLimited {
+            retry_after_ms: 250,
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "code": "rate_limited",
+                "message": "too many requests, retry in 250 ms",
+                "retry_after_ms": 250
+            })
+        );
+    }
+}
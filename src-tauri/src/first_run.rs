@@ -0,0 +1,145 @@
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::{main_window, recovery, settings, version};
+
+const FIRST_RUN_EVENT: &str = "first-run";
+const APP_UPGRADED_EVENT: &str = "app-upgraded";
+const APP_DOWNGRADED_EVENT: &str = "app-downgraded";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Launch {
+    FirstRun { version: String },
+    Upgraded { from: String, to: String },
+    Downgraded { from: String, to: String },
+}
+
+/// What this launch is, until the main window has loaded to be told.
+#[derive(Default)]
+pub struct PendingLaunch(Mutex<Option<Launch>>);
+
+/// Decides what kind of launch this is. `fresh_store` is whether the store
+/// held nothing before this launch, which tells a new install from one that
+/// predates install tracking. A `recovered` store is empty because a corrupt
+/// one was set aside, which `store-recovered` already reports, so it is no
+/// first run. Versions that can't be compared count as an upgrade.
+fn classify(
+    installed: bool,
+    fresh_store: bool,
+    recovered: bool,
+    last_version: Option<&str>,
+    current: &str,
+) -> Option<Launch> {
+    if !installed && fresh_store && !recovered {
+        return Some(Launch::FirstRun {
+            version: current.to_string(),
+        });
+    }
+    let last = last_version.filter(|last| *last != current)?;
+    let (from, to) = (last.to_string(), current.to_string());
+    match version::compare(current, last) {
+        Ok(order) if order.is_lt() => Some(Launch::Downgraded { from, to }),
+        Ok(order) if order.is_eq() => None,
+        _ => Some(Launch::Upgraded { from, to }),
+    }
+}
+
+/// Records the install on first run and the version of every launch, and
+/// queues `first-run`, `app-upgraded` or `app-downgraded` for the frontend.
+pub fn init(app: &AppHandle) {
+    let current = env!("CARGO_PKG_VERSION");
+    let installed = settings::get::<u64>(app, settings::INSTALL_TIMESTAMP).is_some();
+    // Migrations always write the schema version, so ignore it here.
+    let fresh_store = settings::keys(app)
+        .iter()
+        .all(|key| key == settings::SCHEMA_VERSION);
+    let last_version = settings::get::<String>(app, settings::LAST_RUN_VERSION);
+
+    if !installed {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let _ = settings::set(app, settings::INSTALL_TIMESTAMP, now);
+        let _ = settings::set(app, settings::INSTALL_VERSION, current);
+    }
+    if last_version.as_deref() != Some(current) {
+        let _ = settings::set(app, settings::LAST_RUN_VERSION, current);
+    }
+
+    let launch = classify(
+        installed,
+        fresh_store,
+        recovery::was_recovered(app),
+        last_version.as_deref(),
+        current,
+    );
+    match &launch {
+        Some(Launch::FirstRun { version }) => log::info!("first run of {version}"),
+        Some(Launch::Upgraded { from, to }) => log::info!("upgraded from {from} to {to}"),
+        Some(Launch::Downgraded { from, to }) => log::warn!("downgraded from {from} to {to}"),
+        None => {}
+    }
+    *app.state::<PendingLaunch>().0.lock().unwrap() = launch;
+}
+
+/// Emits the queued launch event once the main window has loaded.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let launch = webview.state::<PendingLaunch>().0.lock().unwrap().take();
+    let event = match &launch {
+        Some(Launch::FirstRun { .. }) => FIRST_RUN_EVENT,
+        Some(Launch::Upgraded { .. }) => APP_UPGRADED_EVENT,
+        Some(Launch::Downgraded { .. }) => APP_DOWNGRADED_EVENT,
+        None => return,
+    };
+    let _ = webview.emit_to(main_window::LABEL, event, launch);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_a_fresh_install() {
+        assert_eq!(
+            classify(false, true, false, None, "1.2.0"),
+            Some(Launch::FirstRun {
+                version: "1.2.0".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn treats_existing_stores_as_installed() {
+        // Installs from before install tracking: not a first run.
+        assert_eq!(classify(false, false, false, None, "1.2.0"), None);
+        assert_eq!(classify(true, false, false, Some("1.2.0"), "1.2.0"), None);
+        // A corrupt store set aside leaves an empty one behind.
+        assert_eq!(classify(false, true, true, None, "1.2.0"), None);
+    }
+
+    #[test]
+    fn detects_version_changes() {
+        assert_eq!(
+            classify(true, false, false, Some("1.1.0"), "1.2.0"),
+            Some(Launch::Upgraded {
+                from: "1.1.0".to_string(),
+                to: "1.2.0".to_string()
+            })
+        );
+        assert_eq!(
+            classify(true, false, false, Some("1.3.0"), "1.2.0"),
+            Some(Launch::Downgraded {
+                from: "1.3.0".to_string(),
+                to: "1.2.0".to_string()
+            })
+        );
+    }
+}
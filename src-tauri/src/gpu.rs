@@ -0,0 +1,105 @@
+use std::fs;
+
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::error::AppError;
+use crate::settings;
+
+/// Environment the webview reads at startup to render without the GPU.
+#[cfg(target_os = "linux")]
+const FLAGS: &[(&str, &str)] = &[
+    ("WEBKIT_DISABLE_COMPOSITING_MODE", "1"),
+    ("WEBKIT_DISABLE_DMABUF_RENDERER", "1"),
+];
+/// Setting this replaces the arguments wry passes by default, so they are
+/// repeated here.
+#[cfg(windows)]
+const FLAGS: &[(&str, &str)] = &[(
+    "WEBVIEW2_ADDITIONAL_BROWSER_ARGUMENTS",
+    "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection --disable-gpu",
+)];
+/// WKWebView has no switch for it.
+#[cfg(not(any(target_os = "linux", windows)))]
+const FLAGS: &[(&str, &str)] = &[];
+
+/// Lists the [`FLAGS`] this app set, so the instance a restart launches with
+/// them can tell them from ones set by hand.
+const FLAGS_MARKER: &str = "BOLT_GPU_FLAGS_SET";
+
+/// Reads `disable-gpu` straight from the store file, found where tauri puts
+/// the app data of `identifier`: this runs before the app exists, and before
+/// a corrupt store has been set aside.
+fn gpu_disabled(identifier: &str) -> bool {
+    let Some(dir) = dirs::data_dir() else {
+        return false;
+    };
+    fs::read(dir.join(identifier).join(settings::STORE_PATH))
+        .ok()
+        .and_then(|contents| serde_json::from_slice::<Value>(&contents).ok())
+        .and_then(|store| store.get(settings::DISABLE_GPU)?.as_bool())
+        .unwrap_or(false)
+}
+
+/// Turns GPU acceleration off for the webviews when `disable-gpu` is set, and
+/// takes back the flags a previous instance passed on once it is cleared.
+///
+/// Must run first thing in `main`: changing the environment is only sound
+/// before any other thread has started.
+pub fn apply_flags(identifier: &str) {
+    let inherited = std::env::var(FLAGS_MARKER).ok();
+    if gpu_disabled(identifier) {
+        if inherited.is_some() {
+            return;
+        }
+        let mut set = Vec::new();
+        for (name, value) in FLAGS {
+            // Leave flags set by hand alone.
+            if std::env::var_os(name).is_none() {
+                std::env::set_var(name, value);
+                set.push(*name);
+            }
+        }
+        if !set.is_empty() {
+            std::env::set_var(FLAGS_MARKER, set.join(","));
+        }
+    } else if let Some(inherited) = inherited {
+        for name in inherited.split(',') {
+            std::env::remove_var(name);
+        }
+        std::env::remove_var(FLAGS_MARKER);
+    }
+}
+
+/// Turns GPU acceleration of the webview on or off from the next launch, for
+/// GPUs it renders incorrectly on.
+#[tauri::command]
+pub fn set_gpu_acceleration(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    if !enabled && FLAGS.is_empty() {
+        return Err(AppError::InvalidInput(
+            "GPU acceleration can't be turned off on this platform".to_string(),
+        ));
+    }
+    let was_enabled = !settings::get::<bool>(&app, settings::DISABLE_GPU).unwrap_or(false);
+    if enabled {
+        settings::clear(&app, settings::DISABLE_GPU).map_err(AppError::Store)?;
+    } else {
+        settings::update(&app, settings::DISABLE_GPU, true).map_err(AppError::Store)?;
+    }
+    if enabled != was_enabled {
+        log::info!(
+            "GPU acceleration turned {}",
+            if enabled { "on" } else { "off" }
+        );
+        app.dialog()
+            .message("The change takes effect the next time bolt.gives starts.")
+            .title(if enabled {
+                "GPU acceleration turned on"
+            } else {
+                "GPU acceleration turned off"
+            })
+            .show(|_| {});
+    }
+    Ok(())
+}
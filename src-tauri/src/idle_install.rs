@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::Update;
+use tokio::sync::watch;
+
+use crate::{download, lock, main_window, settings, shutdown, updater};
+
+/// How often the idle conditions are checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Idle time before installing unless `auto-install-idle-minutes` is set.
+const DEFAULT_IDLE_MINUTES: u64 = 10;
+/// How long the frontend shows its countdown before the install starts.
+const COUNTDOWN_SECS: u64 = 30;
+
+const COUNTDOWN_EVENT: &str = "idle-install-countdown";
+const CANCELLED_EVENT: &str = "idle-install-cancelled";
+
+/// Set by [`cancel_idle_install`] to stop a running countdown.
+#[derive(Default)]
+pub struct IdleInstall {
+    cancelled: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CountdownPayload {
+    pub version: String,
+    pub seconds: u64,
+}
+
+/// The update to install if the app has been left alone long enough: the
+/// setting is on, the main window isn't focused, nothing has pinged activity
+/// for the idle period, no download or install is running and the update
+/// isn't snoozed.
+fn ready(app: &AppHandle) -> Option<Update> {
+    if !settings::get::<bool>(app, settings::AUTO_INSTALL_WHEN_IDLE).unwrap_or(false) {
+        return None;
+    }
+    let minutes = settings::get::<u64>(app, settings::AUTO_INSTALL_IDLE_MINUTES)
+        .unwrap_or(DEFAULT_IDLE_MINUTES);
+    let focused = app
+        .get_webview_window(main_window::LABEL)
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false);
+    if focused || !lock::idle_for(app, minutes) || download::in_progress(app) {
+        return None;
+    }
+    // "Later" on the update prompt holds off unattended installs too.
+    updater::pending_update(app).filter(|update| !updater::snoozed(app, update))
+}
+
+/// Counts down, then installs `update` and restarts with the window where it
+/// was. Stops if the user cancels or comes back in the meantime.
+async fn count_down(app: &AppHandle, update: Update, stop: &mut watch::Receiver<bool>) {
+    let state = app.state::<IdleInstall>();
+    state.cancelled.store(false, Ordering::SeqCst);
+    log::info!(
+        "installing update {} in {COUNTDOWN_SECS}s unless cancelled",
+        update.version
+    );
+    let _ = app.emit_to(
+        main_window::LABEL,
+        COUNTDOWN_EVENT,
+        CountdownPayload {
+            version: update.version.clone(),
+            seconds: COUNTDOWN_SECS,
+        },
+    );
+    for _ in 0..COUNTDOWN_SECS {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+            _ = shutdown::requested(stop) => return,
+        }
+        if state.cancelled.load(Ordering::SeqCst) || ready(app).is_none() {
+            log::info!("idle update install cancelled");
+            let _ = app.emit_to(main_window::LABEL, CANCELLED_EVENT, ());
+            return;
+        }
+    }
+    log::info!("installing update {} while idle", update.version);
+    if let Err(e) = updater::install_and_restart(app, &update).await {
+        log::warn!("failed to install the update while idle: {e}");
+        // Wait for another idle period rather than retrying every poll.
+        lock::record_activity(app);
+    }
+}
+
+/// Installs an available update once the app has been idle for
+/// `auto-install-idle-minutes`, if `auto-install-when-idle` is on. The settings
+/// are re-read on every poll, so changes apply without a restart.
+pub async fn run(app: AppHandle) {
+    let mut stop = shutdown::subscribe(&app);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Some(update) = ready(&app) {
+                    count_down(&app, update, &mut stop).await;
+                }
+            }
+            _ = shutdown::requested(&mut stop) => return,
+        }
+    }
+}
+
+/// Stops the countdown to an idle install. Counts as activity, so the next
+/// attempt waits for a full idle period again.
+#[tauri::command]
+pub fn cancel_idle_install(app: AppHandle) {
+    app.state::<IdleInstall>()
+        .cancelled
+        .store(true, Ordering::SeqCst);
+    lock::record_activity(&app);
+}
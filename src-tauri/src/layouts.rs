@@ -0,0 +1,80 @@
+use tauri::AppHandle;
+
+use crate::bounds::{self, WindowBounds};
+use crate::error::AppError;
+use crate::{main_window, settings};
+
+const MAX_NAME_LEN: usize = 64;
+
+/// Layout names become part of a store key, so only a plain set of characters
+/// is allowed; in particular no `/`.
+fn validate_name(name: &str) -> Result<(), AppError> {
+    let valid = !name.trim().is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "layout name \"{name}\" must be 1-{MAX_NAME_LEN} letters, digits, spaces, '-' or '_'"
+        )))
+    }
+}
+
+fn store_key(name: &str) -> String {
+    format!("{}/{name}", settings::LAYOUTS)
+}
+
+/// Saves the main window's current geometry as the layout `name`, replacing
+/// any layout with that name.
+#[tauri::command]
+pub fn save_layout(app: AppHandle, name: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+    let bounds = bounds::capture(&main_window::window(&app)?).map_err(AppError::Window)?;
+    settings::set(&app, &store_key(&name), bounds).map_err(AppError::Store)
+}
+
+/// Moves the main window to the layout `name`, fitted to the current monitors.
+#[tauri::command]
+pub fn apply_layout(app: AppHandle, name: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+    let bounds = settings::get::<WindowBounds>(&app, &store_key(&name))
+        .ok_or_else(|| AppError::InvalidInput(format!("no layout named \"{name}\"")))?;
+    bounds::apply(&main_window::window(&app)?, &bounds).map_err(AppError::Window)
+}
+
+/// Names of the saved layouts, sorted.
+#[tauri::command]
+pub fn list_layouts(app: AppHandle) -> Vec<String> {
+    let mut names = settings::scopes(&app, settings::LAYOUTS);
+    names.sort();
+    names
+}
+
+#[tauri::command]
+pub fn delete_layout(app: AppHandle, name: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+    settings::remove(&app, &store_key(&name)).map_err(AppError::Store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_names() {
+        assert!(validate_name("coding").is_ok());
+        assert!(validate_name("Code Review_2").is_ok());
+    }
+
+    #[test]
+    fn rejects_names_that_could_escape_the_key() {
+        assert!(validate_name("").is_err());
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name("../window-bounds").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{settings, shutdown};
+
+/// How often the idle time is compared against `auto-lock-minutes`.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+const LOCK_EVENT: &str = "lock";
+const UNLOCK_EVENT: &str = "unlock";
+
+/// Last activity as wall-clock milliseconds, so time spent asleep or in the
+/// background counts as idle.
+#[derive(Default)]
+pub struct AutoLock {
+    last_activity_ms: AtomicU64,
+    locked: AtomicBool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockPayload {
+    /// Whether the frontend should blur the window behind its lock screen.
+    pub blur: bool,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Whether `minutes` of idle time have passed since `last_activity_ms`; `0`
+/// minutes never locks.
+fn is_idle(last_activity_ms: u64, now_ms: u64, minutes: u64) -> bool {
+    minutes > 0 && now_ms.saturating_sub(last_activity_ms) >= minutes.saturating_mul(60_000)
+}
+
+/// Resets the idle timer. Activity doesn't unlock; only [`unlock`] does.
+pub fn record_activity(app: &AppHandle) {
+    app.state::<AutoLock>()
+        .last_activity_ms
+        .store(now_ms(), Ordering::SeqCst);
+}
+
+/// Whether there has been no activity for `minutes`; `0` minutes never is.
+pub fn idle_for(app: &AppHandle, minutes: u64) -> bool {
+    let last = app
+        .state::<AutoLock>()
+        .last_activity_ms
+        .load(Ordering::SeqCst);
+    is_idle(last, now_ms(), minutes)
+}
+
+fn lock(app: &AppHandle) {
+    if app.state::<AutoLock>().locked.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    log::info!("locking after inactivity");
+    let blur = settings::get::<bool>(app, settings::AUTO_LOCK_BLUR).unwrap_or(false);
+    // Every window, since any of them can show sensitive content.
+    let _ = app.emit(LOCK_EVENT, LockPayload { blur });
+}
+
+/// Locks the app once it has been idle for `auto-lock-minutes`, which is off
+/// unless set. The setting is re-read on every poll, so changes apply without
+/// a restart.
+pub async fn run(app: AppHandle) {
+    record_activity(&app);
+    let mut stop = shutdown::subscribe(&app);
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let minutes = settings::get::<u64>(&app, settings::AUTO_LOCK_MINUTES).unwrap_or(0);
+                if idle_for(&app, minutes) {
+                    lock(&app);
+                }
+            }
+            _ = shutdown::requested(&mut stop) => return,
+        }
+    }
+}
+
+/// Called by the frontend on user input to keep the app from locking.
+#[tauri::command]
+pub fn activity_ping(app: AppHandle) {
+    record_activity(&app);
+}
+
+/// Leaves the lock screen and restarts the idle timer. Re-authenticating is
+/// up to the frontend.
+#[tauri::command]
+pub fn unlock(app: AppHandle) {
+    record_activity(&app);
+    if app.state::<AutoLock>().locked.swap(false, Ordering::SeqCst) {
+        let _ = app.emit(UNLOCK_EVENT, ());
+    }
+}
+
+/// Whether the app is locked, for a frontend that reloaded while locked.
+#[tauri::command]
+pub fn is_locked(state: State<'_, AutoLock>) -> bool {
+    state.locked.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_after_the_idle_period() {
+        let minute = 60_000;
+        assert!(!is_idle(0, 4 * minute, 5));
+        assert!(is_idle(0, 5 * minute, 5));
+        assert!(is_idle(minute, 10 * minute, 5));
+    }
+
+    #[test]
+    fn never_locks_when_disabled() {
+        assert!(!is_idle(0, u64::MAX, 0));
+        // A clock that went backwards isn't idle time.
+        assert!(!is_idle(10_000, 0, 1));
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{LevelFilter, Record};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_log::{fern, Target, TargetKind};
+
+use crate::error::AppError;
+use crate::settings;
+
+/// Base name passed to the log plugin's `LogDir` target.
+pub const LOG_FILE_NAME: &str = "bolt-gives";
+
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+const DEFAULT_MAX_SIZE_MB: u64 = 5;
+/// Largest `log-max-size-mb` honored, since the setting can hold any number.
+const MAX_SIZE_MB: u64 = 1024;
+/// Rotated logs kept next to the active one, as `bolt-gives.1.log` (newest) and up.
+const MAX_BACKUPS: usize = 3;
+
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Entries kept in memory for the in-app log viewer.
+const LIVE_LOG_CAPACITY: usize = 1000;
+
+/// The most recent log records, for `get_live_logs`. It's fed from the logger,
+/// which has no app handle to reach managed state through.
+static LIVE_LOGS: Mutex<LiveLogs> = Mutex::new(LiveLogs::new());
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    /// Increases by one per record, starting at 1.
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// A bounded buffer of log entries; the oldest are dropped first.
+struct LiveLogs {
+    last_seq: u64,
+    entries: VecDeque<LogEntry>,
+}
+
+impl LiveLogs {
+    const fn new() -> Self {
+        Self {
+            last_seq: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, timestamp_ms: u64, level: String, target: String, message: String) {
+        if self.entries.len() >= LIVE_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.last_seq += 1;
+        self.entries.push_back(LogEntry {
+            seq: self.last_seq,
+            timestamp_ms,
+            level,
+            target,
+            message,
+        });
+    }
+
+    fn since(&self, seq: u64) -> Vec<LogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.seq > seq)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Drops the `<date>[target][LEVEL] ` prefix the log plugin puts on every
+/// record before its targets see it.
+fn strip_prefix<'a>(line: &'a str, target: &str, level: log::Level) -> &'a str {
+    let prefix = format!("[{target}][{level}] ");
+    line.find(&prefix)
+        .map_or(line, |start| &line[start + prefix.len()..])
+}
+
+fn record_live(record: &Record) {
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let line = record.args().to_string();
+    let message = strip_prefix(&line, record.target(), record.level()).to_string();
+    LIVE_LOGS.lock().unwrap().push(
+        timestamp_ms,
+        record.level().as_str().to_lowercase(),
+        record.target().to_string(),
+        message,
+    );
+}
+
+fn active_path(log_dir: &Path) -> PathBuf {
+    log_dir.join(format!("{LOG_FILE_NAME}.log"))
+}
+
+fn backup_path(log_dir: &Path, index: usize) -> PathBuf {
+    log_dir.join(format!("{LOG_FILE_NAME}.{index}.log"))
+}
+
+/// Moves the active log aside once it is larger than `max_size` bytes, shifting
+/// existing backups up by one and dropping any beyond `max_files`.
+fn rotate_logs(log_dir: &Path, max_size: u64, max_files: usize) -> io::Result<()> {
+    let current = active_path(log_dir);
+    match fs::metadata(&current) {
+        Ok(metadata) if metadata.len() > max_size => {}
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+
+    if max_files == 0 {
+        return fs::remove_file(&current);
+    }
+    let oldest = backup_path(log_dir, max_files);
+    if oldest.exists() {
+        fs::remove_file(&oldest)?;
+    }
+    for index in (1..max_files).rev() {
+        let backup = backup_path(log_dir, index);
+        if backup.exists() {
+            fs::rename(&backup, backup_path(log_dir, index + 1))?;
+        }
+    }
+    fs::rename(&current, backup_path(log_dir, 1))
+}
+
+/// Whether `name` is a rotated log, `bolt-gives.<n>.log`.
+fn is_rotated_log(name: &str) -> bool {
+    name.strip_prefix(LOG_FILE_NAME)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|rest| rest.strip_suffix(".log"))
+        .is_some_and(|index| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Regular files directly in `log_dir`. Symlinks are skipped so nothing
+/// outside the directory is counted or deleted.
+fn log_files(log_dir: &Path) -> io::Result<Vec<(PathBuf, u64)>> {
+    let entries = match fs::read_dir(log_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = fs::symlink_metadata(entry.path())?;
+        if metadata.is_file() {
+            files.push((entry.path(), metadata.len()));
+        }
+    }
+    Ok(files)
+}
+
+/// Deletes the rotated logs in `log_dir`, keeping the active one; returns the
+/// bytes freed.
+fn clear_rotated_logs(log_dir: &Path) -> io::Result<u64> {
+    let mut freed = 0;
+    for (path, len) in log_files(log_dir)? {
+        let rotated = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(is_rotated_log);
+        if rotated {
+            fs::remove_file(&path)?;
+            freed += len;
+        }
+    }
+    Ok(freed)
+}
+
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    match level {
+        "error" => Some(LevelFilter::Error),
+        "warn" => Some(LevelFilter::Warn),
+        "info" => Some(LevelFilter::Info),
+        "debug" => Some(LevelFilter::Debug),
+        "trace" => Some(LevelFilter::Trace),
+        _ => None,
+    }
+}
+
+/// Rotates the log file and registers the log plugin.
+///
+/// This runs in `setup` rather than on the builder because the size limit
+/// and level live in the store.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let max_size_mb = settings::get::<u64>(app, settings::LOG_MAX_SIZE_MB)
+        .unwrap_or(DEFAULT_MAX_SIZE_MB)
+        .min(MAX_SIZE_MB);
+    let log_dir = app.path().app_log_dir()?;
+    if let Err(e) = rotate_logs(
+        &log_dir,
+        max_size_mb.saturating_mul(1024 * 1024),
+        MAX_BACKUPS,
+    ) {
+        // The logger isn't up yet.
+        eprintln!("failed to rotate logs: {e}");
+    }
+
+    app.plugin(
+        tauri_plugin_log::Builder::new()
+            .targets([
+                Target::new(TargetKind::Stdout),
+                Target::new(TargetKind::LogDir {
+                    file_name: Some(LOG_FILE_NAME.to_string()),
+                }),
+                Target::new(TargetKind::Webview),
+                Target::new(TargetKind::Dispatch(
+                    fern::Dispatch::new().chain(fern::Output::call(record_live)),
+                )),
+            ])
+            // Rotation happens above on launch; the plugin's own would delete
+            // the whole log every 40 KB.
+            .max_file_size(u128::MAX)
+            .build(),
+    )?;
+
+    apply_level(app);
+    Ok(())
+}
+
+/// Applies the stored log level, or the default if unset.
+///
+/// The plugin itself passes everything through; the global max level does the
+/// filtering so the level can change without a restart.
+pub fn apply_level(app: &AppHandle) {
+    let level = settings::get::<String>(app, settings::LOG_LEVEL)
+        .and_then(|level| parse_level(&level))
+        .unwrap_or(DEFAULT_LEVEL);
+    log::set_max_level(level);
+}
+
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, level: String) -> Result<(), String> {
+    let filter = parse_level(&level).ok_or_else(|| format!("unknown log level \"{level}\""))?;
+    settings::update(&app, settings::LOG_LEVEL, &level)?;
+    log::set_max_level(filter);
+    Ok(())
+}
+
+pub fn log_file_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    Ok(active_path(&dir))
+}
+
+/// Returns the last `max_lines` lines of `reader`, reading backwards from the
+/// end in chunks so large logs are never loaded whole.
+fn tail_lines<R: Read + Seek>(reader: &mut R, max_lines: usize) -> io::Result<Vec<String>> {
+    if max_lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut pos = reader.seek(SeekFrom::End(0))?;
+    let mut buf = Vec::new();
+    // One extra newline is needed when the file ends with one.
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= max_lines {
+        let len = TAIL_CHUNK_SIZE.min(pos);
+        pos -= len;
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; len as usize];
+        reader.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    Ok(lines[start..].iter().map(|line| line.to_string()).collect())
+}
+
+#[tauri::command]
+pub fn get_recent_logs(app: AppHandle, max_lines: usize) -> Result<Vec<String>, String> {
+    let path = log_file_path(&app)?;
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    tail_lines(&mut file, max_lines).map_err(|e| e.to_string())
+}
+
+/// Total size of the files in the log directory, in bytes.
+#[tauri::command]
+pub fn get_log_dir_size(app: AppHandle) -> Result<u64, AppError> {
+    let dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(log_files(&dir)?.iter().map(|(_, len)| len).sum())
+}
+
+/// Deletes the rotated logs, keeping the active one, and returns the bytes freed.
+#[tauri::command]
+pub fn clear_logs(app: AppHandle) -> Result<u64, AppError> {
+    log::logger().flush();
+    let dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let freed = clear_rotated_logs(&dir)?;
+    log::info!("cleared {freed} bytes of old logs");
+    Ok(freed)
+}
+
+/// Log entries newer than `since_seq`, oldest first, from the last
+/// [`LIVE_LOG_CAPACITY`] kept in memory. Pass the `seq` of the last entry seen
+/// (or 0) to poll for new ones.
+#[tauri::command]
+pub fn get_live_logs(since_seq: u64) -> Vec<LogEntry> {
+    LIVE_LOGS.lock().unwrap().since(since_seq)
+}
+
+#[tauri::command]
+pub fn get_log_file_path(app: AppHandle) -> Result<String, String> {
+    Ok(log_file_path(&app)?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_live_logs() {
+        let mut logs = LiveLogs::new();
+        for i in 0..LIVE_LOG_CAPACITY + 5 {
+            logs.push(
+                0,
+                "info".to_string(),
+                "app".to_string(),
+                format!("entry {i}"),
+            );
+        }
+        assert_eq!(logs.entries.len(), LIVE_LOG_CAPACITY);
+        assert_eq!(logs.entries[0].seq, 6);
+        let newest = logs.since(LIVE_LOG_CAPACITY as u64 + 3);
+        assert_eq!(newest.len(), 2);
+        assert_eq!(
+            newest[1].message,
+            format!("entry {}", LIVE_LOG_CAPACITY + 4)
+        );
+        assert!(logs.since(LIVE_LOG_CAPACITY as u64 + 5).is_empty());
+    }
+
+    #[test]
+    fn strips_the_plugin_prefix() {
+        assert_eq!(
+            strip_prefix(
+                "[2025-01-02][10:11:12][bolt_gives::updater][WARN] offline [again]",
+                "bolt_gives::updater",
+                log::Level::Warn
+            ),
+            "offline [again]"
+        );
+        assert_eq!(
+            strip_prefix("plain message", "app", log::Level::Info),
+            "plain message"
+        );
+    }
+
+    #[test]
+    fn tails_across_chunks() {
+        let text: String = (0..5000).map(|i| format!("line {i}\n")).collect();
+        let lines = tail_lines(&mut Cursor::new(text), 3).unwrap();
+        assert_eq!(lines, ["line 4997", "line 4998", "line 4999"]);
+    }
+
+    #[test]
+    fn rotation_shifts_backups_and_drops_the_oldest() {
+        let dir = std::env::temp_dir().join(format!("bolt-gives-rotate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let current = active_path(&dir);
+        fs::write(&current, "current").unwrap();
+        for index in 1..=3 {
+            fs::write(backup_path(&dir, index), format!("backup {index}")).unwrap();
+        }
+
+        // Under the limit: nothing moves.
+        rotate_logs(&dir, 100, 3).unwrap();
+        assert_eq!(fs::read_to_string(&current).unwrap(), "current");
+
+        rotate_logs(&dir, 4, 3).unwrap();
+        assert!(!current.exists());
+        assert_eq!(fs::read_to_string(backup_path(&dir, 1)).unwrap(), "current");
+        assert_eq!(
+            fs::read_to_string(backup_path(&dir, 2)).unwrap(),
+            "backup 1"
+        );
+        assert_eq!(
+            fs::read_to_string(backup_path(&dir, 3)).unwrap(),
+            "backup 2"
+        );
+        assert!(!backup_path(&dir, 4).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clearing_keeps_the_active_log_and_other_files() {
+        let dir = std::env::temp_dir().join(format!("bolt-gives-clear-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(active_path(&dir), "active").unwrap();
+        fs::write(backup_path(&dir, 1), "12345").unwrap();
+        fs::write(backup_path(&dir, 2), "123").unwrap();
+        fs::write(dir.join("notes.txt"), "keep").unwrap();
+        fs::write(dir.join("bolt-gives.old.log"), "keep").unwrap();
+
+        assert_eq!(clear_rotated_logs(&dir).unwrap(), 8);
+        assert!(active_path(&dir).exists());
+        assert!(!backup_path(&dir, 1).exists());
+        assert!(!backup_path(&dir, 2).exists());
+        assert!(dir.join("notes.txt").exists());
+        assert!(dir.join("bolt-gives.old.log").exists());
+        assert_eq!(clear_rotated_logs(&dir).unwrap(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recognizes_rotated_log_names() {
+        assert!(is_rotated_log("bolt-gives.1.log"));
+        assert!(is_rotated_log("bolt-gives.12.log"));
+        assert!(!is_rotated_log("bolt-gives.log"));
+        assert!(!is_rotated_log("bolt-gives..log"));
+        assert!(!is_rotated_log("other.1.log"));
+    }
+
+    #[test]
+    fn rotation_ignores_a_missing_log() {
+        let dir = std::env::temp_dir().join("bolt-gives-rotate-missing");
+        assert!(rotate_logs(&dir, 0, 3).is_ok());
+    }
+
+    #[test]
+    fn returns_everything_for_short_files() {
+        let lines = tail_lines(&mut Cursor::new("a\nb"), 10).unwrap();
+        assert_eq!(lines, ["a", "b"]);
+        assert!(tail_lines(&mut Cursor::new(""), 10).unwrap().is_empty());
+    }
+}
@@ -1,31 +1,334 @@
-use tauri::Manager;
+use tauri::webview::PageLoadEvent;
+use tauri::{Manager, RunEvent, WindowEvent};
 
+mod autostart;
+mod backup;
+mod bounds;
+mod clipboard;
 mod commands;
+mod crash;
+mod deep_link;
+mod devtools;
+mod diagnostics;
+mod disk;
+mod download;
+mod env_config;
+mod error;
+mod first_run;
+mod gpu;
+mod http;
+mod idle_install;
+mod layouts;
+mod lock;
+mod logs;
+mod main_window;
+mod menu;
+mod migrations;
+mod mock_update;
+mod notifications;
+mod persistence;
+mod post_update;
+mod progress;
+mod rate_limit;
+mod recents;
+mod recovery;
+mod reminders;
+mod route;
+mod safe_mode;
+mod screenshot;
+mod secrets;
+mod settings;
+mod shortcut;
+mod shutdown;
+mod splash;
+mod system;
+mod tasks;
+mod telemetry;
+mod theme;
+mod title;
+mod tray;
+mod updater;
+mod version;
+mod wake_lock;
+mod window_state;
+mod windows;
+mod zoom;
 
 fn main() {
+    crash::install_hook();
+    let context = tauri::generate_context!();
+    // Before the builder starts any threads, and so before any window exists.
+    gpu::apply_flags(&context.config().identifier);
+
     tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.set_focus();
-            }
+        // Registered first so a second launch exits before touching the store.
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            main_window::show(app);
+            main_window::forward_launch(app, args, cwd);
         }))
+        .plugin(autostart::plugin())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_window_state::Builder::default().build())
+        .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(bounds::BoundsWriter::default())
+        .manage(bounds::WarmRestart::default())
+        .manage(progress::TaskbarProgress::default())
+        .manage(wake_lock::WakeLock::default())
+        .manage(mock_update::MockUpdater::default())
+        .manage(reminders::Reminders::default())
+        .manage(persistence::Persistence::default())
+        .manage(main_window::ExitFlag::default())
+        .manage(route::RouteRestored::default())
+        .manage(deep_link::DeepLinkQueue::default())
+        .manage(crash::CrashReports::default())
+        .manage(first_run::PendingLaunch::default())
+        .manage(lock::AutoLock::default())
+        .manage(idle_install::IdleInstall::default())
+        .manage(download::ActiveDownloads::default())
+        .manage(rate_limit::RateLimiter::default())
+        .manage(recovery::RecoveredStore::default())
+        .manage(safe_mode::SafeMode::default())
+        .manage(settings::StoreWriter::default())
+        .manage(shutdown::Shutdown::default())
+        .manage(tasks::BackgroundTasks::default())
+        .manage(telemetry::TelemetryQueue::default())
+        .manage(splash::MainRevealed::default())
+        .manage(notifications::PendingClick::default())
+        .manage(updater::AvailableUpdate::default())
+        .manage(updater::UpdateInProgress::default())
         .invoke_handler(tauri::generate_handler![
             commands::get_app_version,
+            commands::get_build_info,
+            system::get_system_info,
             commands::get_app_path,
-            commands::open_external_link,
+            commands::open_external,
+            commands::reveal_in_folder,
+            diagnostics::collect_diagnostics,
+            devtools::open_devtools,
+            devtools::close_devtools,
+            disk::get_free_disk_space,
+            download::download_file,
+            download::cancel_downloads,
+            screenshot::capture_window,
+            http::fetch_text,
+            http::set_http_proxy,
+            autostart::set_autostart,
+            autostart::get_autostart,
+            backup::restore_backup,
+            bounds::list_monitors,
+            bounds::move_to_monitor,
+            bounds::center_window,
+            progress::set_progress_bar,
+            wake_lock::set_wake_lock,
+            recents::add_recent,
+            recents::get_recents,
+            recents::clear_recents,
+            reminders::schedule_reminder,
+            reminders::cancel_reminder,
+            clipboard::copy_to_clipboard,
+            clipboard::read_clipboard,
+            updater::check_for_updates,
+            updater::show_update_dialog,
+            updater::snooze_update,
+            updater::install_update,
+            updater::defer_update,
+            updater::is_update_in_progress,
+            updater::get_last_update_check,
+            updater::set_update_channel,
+            updater::get_update_endpoints,
+            updater::set_update_endpoints,
+            updater::test_update_connectivity,
+            mock_update::set_mock_update,
+            version::compare_versions,
+            version::is_major_upgrade,
+            updater::set_update_check_interval,
+            tray::set_minimize_to_tray,
+            layouts::save_layout,
+            layouts::apply_layout,
+            layouts::list_layouts,
+            layouts::delete_layout,
+            lock::activity_ping,
+            lock::unlock,
+            lock::is_locked,
+            idle_install::cancel_idle_install,
+            logs::get_recent_logs,
+            logs::get_log_file_path,
+            logs::get_live_logs,
+            logs::get_log_dir_size,
+            logs::clear_logs,
+            logs::set_log_level,
+            main_window::quit_app,
+            main_window::is_window_focused,
+            main_window::restart_app,
+            main_window::reload_webview,
+            main_window::set_close_behavior,
+            main_window::get_close_behavior,
+            notifications::notify,
+            route::save_last_route,
+            safe_mode::restart_safe_mode,
+            safe_mode::is_safe_mode,
+            secrets::set_secret,
+            secrets::get_secret,
+            settings::get_setting,
+            settings::set_setting,
+            settings::subscribe_settings,
+            settings::export_settings,
+            settings::import_settings,
+            settings::apply_profile_url,
+            settings::reset_settings,
+            shortcut::set_toggle_shortcut,
+            splash::close_splashscreen,
+            tasks::restart_background_tasks,
+            telemetry::set_telemetry_enabled,
+            telemetry::is_telemetry_enabled,
+            telemetry::track_event,
+            theme::set_theme,
+            theme::set_window_background_color,
+            title::set_window_title,
+            title::get_window_title,
+            window_state::set_always_on_top,
+            window_state::set_visible_on_all_workspaces,
+            window_state::set_resizable,
+            window_state::set_decorations,
+            window_state::set_window_opacity,
+            window_state::get_window_state,
+            windows::open_window,
+            windows::list_windows,
+            windows::broadcast,
+            gpu::set_gpu_acceleration,
+            zoom::set_zoom,
+            zoom::get_zoom,
         ])
         .setup(|app| {
-            #[cfg(debug_assertions)]
+            let handle = app.handle();
+            // Before anything else reads the store.
+            let recovered = recovery::run(handle);
+            let migrated = migrations::run(handle);
+            logs::init(handle)?;
+            app.manage(env_config::load_env_config(handle));
+            // The environment config may set the log level.
+            logs::apply_level(handle);
+            persistence::init(handle);
+            match recovered {
+                Ok(Some(path)) => log::error!(
+                    "settings store was corrupt, moved it to {} and started from defaults",
+                    path.display()
+                ),
+                Ok(None) => {}
+                Err(e) => log::error!("failed to check the settings store: {e}"),
+            }
+            match migrated {
+                Ok(steps) => steps
+                    .iter()
+                    .for_each(|step| log::info!("migrated settings: {step}")),
+                Err(e) => log::error!("failed to migrate settings: {e}"),
+            }
+            if let Err(e) = main_window::ensure(handle) {
+                log::error!("{e}");
+                return Err(e.into());
+            }
+            bounds::take_warm_restart(handle);
+            if safe_mode::detect(handle) {
+                log::warn!("starting in safe mode");
+            }
+            crash::init(handle);
+            // Before first_run::init records this version as the last run.
+            post_update::run(handle);
+            first_run::init(handle);
+            reminders::init(handle);
+
+            // A hidden launch needs the tray, or there'd be no way to open the window.
+            let hidden = autostart::starts_hidden(std::env::args());
+            if hidden || settings::get::<bool>(handle, settings::MINIMIZE_TO_TRAY).unwrap_or(false)
             {
-                if let Some(window) = app.get_webview_window("main") {
-                    window.open_devtools();
-                }
+                tray::create(handle)?;
+            }
+            menu::init(handle)?;
+
+            updater::clear_deferred(handle);
+            app.manage(updater::CheckSchedule::new(handle));
+            tasks::spawn_all(handle);
+
+            // The main window loads hidden behind the splash; it is shown at its
+            // restored bounds once the frontend calls `close_splashscreen`.
+            if let Some(window) = app
+                .get_webview_window(main_window::LABEL)
+                .filter(|_| !safe_mode::is_active(handle))
+            {
+                let _ = theme::apply(&window.as_ref().window());
+                // Before the window is first shown, to avoid a white flash.
+                let _ = theme::apply_background(&window);
+                zoom::restore(&window);
+            }
+            if hidden {
+                log::info!("launched hidden, staying in the tray");
             }
+            title::restore(handle);
+            splash::start(handle, hidden);
+
+            autostart::reconcile(handle);
+            deep_link::init(handle);
+            shortcut::init(handle);
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_page_load(|webview, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                route::restore(webview);
+                deep_link::flush(webview);
+                crash::flush(webview);
+                recovery::flush(webview);
+                safe_mode::flush(webview);
+                first_run::flush(webview);
+                persistence::flush(webview);
+            }
+        })
+        .on_window_event(|window, event| match event {
+            WindowEvent::Moved(_) => bounds::schedule_save(window),
+            // A pinned size is the layout's, not one the user picked.
+            WindowEvent::Resized(_) if window.is_resizable().unwrap_or(true) => {
+                bounds::schedule_save(window)
+            }
+            WindowEvent::CloseRequested { api, .. } => {
+                let _ = bounds::save(window);
+                main_window::on_close_requested(window, api);
+            }
+            // Don't lose a save that is still waiting out its debounce.
+            WindowEvent::Destroyed => {
+                let _ = settings::flush(window.app_handle());
+            }
+            WindowEvent::Focused(focused) => {
+                if *focused {
+                    notifications::on_focus(window);
+                }
+                main_window::on_focus_changed(window, *focused);
+                if window.label() == main_window::LABEL {
+                    shortcut::on_focus_changed(window.app_handle(), *focused);
+                }
+                shortcut::on_zoom_focus_changed(window.app_handle(), *focused);
+                lock::record_activity(window.app_handle());
+            }
+            WindowEvent::ThemeChanged(new_theme) => theme::on_theme_changed(window, *new_theme),
+            _ => {}
+        })
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            RunEvent::ExitRequested { code, api, .. } => {
+                main_window::set_exiting(app, true);
+                if updater::install_on_exit(app, code.unwrap_or(0)) {
+                    api.prevent_exit();
+                }
+            }
+            RunEvent::Exit => {
+                telemetry::flush_on_exit(app);
+                shutdown::shutdown(app);
+            }
+            _ => {}
+        });
 }
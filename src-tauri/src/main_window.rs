@@ -0,0 +1,252 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, CloseRequestApi, Emitter, Manager, WebviewWindowBuilder, Window};
+
+use crate::error::AppError;
+use crate::{bounds, settings, shutdown, splash, tray, updater};
+
+/// Label of the primary window declared in `tauri.conf.json`.
+pub const LABEL: &str = "main";
+
+const SECOND_INSTANCE_EVENT: &str = "second-instance";
+const WINDOW_FOCUS_EVENT: &str = "window-focus";
+const WEBVIEW_RELOADING_EVENT: &str = "webview-reloading";
+
+/// Time the frontend gets to save transient state after `webview-reloading`.
+const RELOAD_GRACE: Duration = Duration::from_millis(250);
+
+/// Command line of a launch that was redirected to the running instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchPayload {
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+/// What closing the main window does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CloseBehavior {
+    /// Quits the whole app, secondary windows included: they are views onto
+    /// the main window's session and don't outlive it.
+    Quit,
+    Minimize,
+    MinimizeToTray,
+}
+
+impl CloseBehavior {
+    /// What each platform's users expect: Mac apps keep running when their
+    /// window is closed, Windows and Linux ones quit.
+    const fn platform_default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::Minimize
+        } else {
+            Self::Quit
+        }
+    }
+}
+
+/// Set once the app is really shutting down so closing the window is no
+/// longer turned into hiding it.
+#[derive(Default)]
+pub struct ExitFlag(AtomicBool);
+
+/// Makes sure the main window exists, recreating it from its entry in
+/// `tauri.conf.json` if it wasn't created. Fails with the open window labels
+/// when that isn't possible, so setup can stop with something to go on.
+pub fn ensure(app: &AppHandle) -> Result<(), String> {
+    if app.get_webview_window(LABEL).is_some() {
+        return Ok(());
+    }
+    let mut open: Vec<String> = app.webview_windows().into_keys().collect();
+    open.sort();
+    log::error!("the \"{LABEL}\" window is missing (open windows: {open:?}), recreating it");
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|config| config.label == LABEL)
+        .ok_or_else(|| {
+            format!("tauri.conf.json declares no \"{LABEL}\" window (open windows: {open:?})")
+        })?;
+    WebviewWindowBuilder::from_config(app, config)
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("failed to recreate the \"{LABEL}\" window: {e}"))?;
+    Ok(())
+}
+
+/// The main window, for commands that act on it.
+pub fn window(app: &AppHandle) -> Result<Window, AppError> {
+    app.get_webview_window(LABEL)
+        .map(|window| window.as_ref().window())
+        .ok_or_else(|| AppError::Window("the main window is not open".to_string()))
+}
+
+/// Marks the app as exiting (or not) for the close-to-tray intercept.
+pub fn set_exiting(app: &AppHandle, exiting: bool) {
+    app.state::<ExitFlag>().0.store(exiting, Ordering::SeqCst);
+}
+
+/// The stored close behavior. Before it existed, `minimize-to-tray` was the
+/// only choice, so that still counts when nothing else has been picked.
+pub fn close_behavior(app: &AppHandle) -> CloseBehavior {
+    settings::get::<CloseBehavior>(app, settings::CLOSE_BEHAVIOR).unwrap_or_else(|| {
+        if settings::get::<bool>(app, settings::MINIMIZE_TO_TRAY).unwrap_or(false) {
+            CloseBehavior::MinimizeToTray
+        } else {
+            CloseBehavior::platform_default()
+        }
+    })
+}
+
+/// Carries out the close behavior when the main window is asked to close;
+/// secondary windows just close. Once the app is exiting the window is
+/// always let go.
+pub fn on_close_requested(window: &Window, api: &CloseRequestApi) {
+    let app = window.app_handle();
+    if window.label() != LABEL || app.state::<ExitFlag>().0.load(Ordering::SeqCst) {
+        return;
+    }
+    api.prevent_close();
+    match close_behavior(app) {
+        CloseBehavior::Quit => quit(app),
+        CloseBehavior::Minimize => {
+            let _ = window.minimize();
+        }
+        CloseBehavior::MinimizeToTray => {
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Exits the app, bypassing the close-to-tray intercept.
+pub fn quit(app: &AppHandle) {
+    set_exiting(app, true);
+    shutdown::shutdown(app);
+    app.exit(0);
+}
+
+/// Exits the app without installing an update that was deferred to quit time.
+pub fn quit_without_update(app: &AppHandle) {
+    updater::clear_deferred(app);
+    quit(app);
+}
+
+/// Relaunches the app after flushing the store, handing the window bounds to
+/// the new instance so it reopens exactly where this one was.
+pub fn restart(app: &AppHandle) -> ! {
+    bounds::prepare_restart(app);
+    let _ = settings::flush(app);
+    set_exiting(app, true);
+    app.restart()
+}
+
+/// Reloads the frontend in the main window, keeping the process and the
+/// window as they are. `webview-reloading` goes out first so the frontend can
+/// save transient state.
+pub fn reload(app: &AppHandle) -> Result<(), AppError> {
+    let webview = app
+        .get_webview_window(LABEL)
+        .ok_or_else(|| AppError::Window("the main window is not open".to_string()))?;
+    let _ = webview.emit(WEBVIEW_RELOADING_EVENT, ());
+    log::info!("reloading the frontend");
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RELOAD_GRACE).await;
+        if let Err(e) = webview.reload() {
+            log::error!("failed to reload the frontend: {e}");
+        }
+    });
+    Ok(())
+}
+
+/// Brings the main window to the front, restoring it if it was hidden or
+/// minimized. While the splash is still up, this reveals the main window in
+/// its place instead.
+pub fn show(app: &AppHandle) {
+    if !splash::is_revealed(app) {
+        splash::reveal(app);
+        return;
+    }
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Hands the arguments of a second launch (e.g. a deep link) to the frontend.
+pub fn forward_launch(app: &AppHandle, args: Vec<String>, cwd: String) {
+    let _ = app.emit_to(LABEL, SECOND_INSTANCE_EVENT, LaunchPayload { args, cwd });
+}
+
+/// Tells the frontend when the main window gains or loses focus. Focus changes
+/// while it is still hidden behind the splash are not reported.
+pub fn on_focus_changed(window: &Window, focused: bool) {
+    if window.label() != LABEL || !splash::is_revealed(window.app_handle()) {
+        return;
+    }
+    let _ = window.emit(WINDOW_FOCUS_EVENT, focused);
+}
+
+/// Hides the main window if it is visible, otherwise shows it.
+pub fn toggle(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(LABEL) else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        show(app);
+    }
+}
+
+/// Quits the app; `skip_update` drops a deferred update instead of installing it.
+#[tauri::command]
+pub fn quit_app(app: AppHandle, skip_update: Option<bool>) {
+    if skip_update.unwrap_or(false) {
+        quit_without_update(&app);
+    } else {
+        quit(&app);
+    }
+}
+
+#[tauri::command]
+pub fn is_window_focused(app: AppHandle) -> bool {
+    app.get_webview_window(LABEL)
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Picks what closing the main window does: `quit`, `minimize` or
+/// `minimize-to-tray`. The tray icon is kept for (only) the last one.
+#[tauri::command]
+pub fn set_close_behavior(app: AppHandle, behavior: String) -> Result<(), AppError> {
+    let behavior: CloseBehavior =
+        serde_json::from_value(behavior.clone().into()).map_err(|_| {
+            AppError::InvalidInput(format!(
+            "unknown close behavior \"{behavior}\", expected quit, minimize or minimize-to-tray"
+        ))
+        })?;
+    tray::set_minimize_to_tray(app.clone(), behavior == CloseBehavior::MinimizeToTray)
+        .map_err(AppError::Window)?;
+    settings::update(&app, settings::CLOSE_BEHAVIOR, behavior).map_err(AppError::Store)
+}
+
+#[tauri::command]
+pub fn get_close_behavior(app: AppHandle) -> CloseBehavior {
+    close_behavior(&app)
+}
+
+/// Reloads the frontend without restarting the app, for when only the webview
+/// is stuck.
+#[tauri::command]
+pub fn reload_webview(app: AppHandle) -> Result<(), AppError> {
+    reload(&app)
+}
+
+#[tauri::command]
+pub fn restart_app(app: AppHandle) {
+    restart(&app);
+}
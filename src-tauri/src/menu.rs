@@ -0,0 +1,224 @@
+use tauri::menu::{
+    MenuBuilder, MenuEvent, MenuItemBuilder, PredefinedMenuItem, Submenu, SubmenuBuilder,
+};
+use tauri::{AppHandle, Wry};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::recents::{self, RecentItem};
+use crate::{bounds, commands, devtools, logs, main_window, settings, updater, zoom};
+
+// Prefixed so they can't collide with the tray menu ids; both menus share
+// the app-wide menu event stream.
+const CHECK_UPDATES_ID: &str = "menu-check-updates";
+const QUIT_ID: &str = "menu-quit";
+const QUIT_WITHOUT_UPDATE_ID: &str = "menu-quit-without-update";
+const ABOUT_ID: &str = "menu-about";
+const VIEW_LOGS_ID: &str = "menu-view-logs";
+const ZOOM_IN_ID: &str = "menu-zoom-in";
+const ZOOM_OUT_ID: &str = "menu-zoom-out";
+const RESET_ZOOM_ID: &str = "menu-reset-zoom";
+const CENTER_WINDOW_ID: &str = "menu-center-window";
+const DEVTOOLS_ACCESS_ID: &str = "menu-devtools-access";
+const FILE_ID: &str = "menu-file";
+const OPEN_RECENT_ID: &str = "menu-open-recent";
+const CLEAR_RECENTS_ID: &str = "menu-clear-recents";
+/// Followed by the project's position in the recents list.
+const RECENT_PREFIX: &str = "menu-recent-";
+
+/// Installs the native menu bar.
+///
+/// macOS always gets it since that's where the standard shortcuts live; on
+/// Windows and Linux it can be turned off with `show-native-menu` when the
+/// frontend draws its own, and the zoom shortcuts are then registered by
+/// [`crate::shortcut::on_zoom_focus_changed`] instead.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let enabled = settings::get::<bool>(app, settings::SHOW_NATIVE_MENU).unwrap_or(true);
+    if !cfg!(target_os = "macos") && !enabled {
+        return Ok(());
+    }
+
+    let quit = MenuItemBuilder::with_id(QUIT_ID, "Quit")
+        .accelerator("CmdOrCtrl+Q")
+        .build(app)?;
+    let quit_without_update =
+        MenuItemBuilder::with_id(QUIT_WITHOUT_UPDATE_ID, "Quit Without Updating")
+            .accelerator("CmdOrCtrl+Alt+Q")
+            .build(app)?;
+    let open_recent = SubmenuBuilder::with_id(app, OPEN_RECENT_ID, "Open Recent").build()?;
+    fill_recents(app, &open_recent, &recents::list(app))?;
+    let file = SubmenuBuilder::with_id(app, FILE_ID, "File")
+        .item(&open_recent)
+        .separator()
+        .text(CHECK_UPDATES_ID, "Check for Updates…")
+        .separator()
+        .close_window()
+        .item(&quit)
+        .item(&quit_without_update)
+        .build()?;
+    let edit = SubmenuBuilder::new(app, "Edit")
+        .undo()
+        .redo()
+        .separator()
+        .cut()
+        .copy()
+        .paste()
+        .select_all()
+        .build()?;
+    let view = SubmenuBuilder::new(app, "View")
+        .item(
+            &MenuItemBuilder::with_id(ZOOM_IN_ID, "Zoom In")
+                .accelerator("CmdOrCtrl+Equal")
+                .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id(ZOOM_OUT_ID, "Zoom Out")
+                .accelerator("CmdOrCtrl+Minus")
+                .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id(RESET_ZOOM_ID, "Actual Size")
+                .accelerator("CmdOrCtrl+0")
+                .build(app)?,
+        )
+        .separator()
+        .text(CENTER_WINDOW_ID, "Center Window")
+        .build()?;
+    let help = SubmenuBuilder::new(app, "Help")
+        .text(ABOUT_ID, "About bolt.gives")
+        .text(VIEW_LOGS_ID, "View Logs")
+        .separator()
+        .text(DEVTOOLS_ACCESS_ID, "Developer Tools Access…")
+        .build()?;
+
+    let menu = MenuBuilder::new(app);
+    // The first submenu becomes the application menu on macOS.
+    #[cfg(target_os = "macos")]
+    let menu = menu.item(&app_submenu(app, &quit)?);
+    let menu = menu.items(&[&file, &edit, &view, &help]).build()?;
+
+    app.set_menu(menu)?;
+    app.on_menu_event(on_menu_event);
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn app_submenu(
+    app: &AppHandle,
+    quit: &tauri::menu::MenuItem<tauri::Wry>,
+) -> tauri::Result<tauri::menu::Submenu<tauri::Wry>> {
+    SubmenuBuilder::new(app, "bolt.gives")
+        .text(ABOUT_ID, "About bolt.gives")
+        .separator()
+        .services()
+        .separator()
+        .hide()
+        .hide_others()
+        .show_all()
+        .separator()
+        .item(quit)
+        .build()
+}
+
+/// Replaces the items of the "Open Recent" submenu with `recents`. Projects
+/// that have gone missing are listed but can't be picked.
+fn fill_recents(
+    app: &AppHandle,
+    submenu: &Submenu<Wry>,
+    recents: &[RecentItem],
+) -> tauri::Result<()> {
+    for _ in 0..submenu.items()?.len() {
+        submenu.remove_at(0)?;
+    }
+    for (index, recent) in recents.iter().enumerate() {
+        let item = MenuItemBuilder::with_id(format!("{RECENT_PREFIX}{index}"), &recent.label)
+            .enabled(recent.exists)
+            .build(app)?;
+        submenu.append(&item)?;
+    }
+    if !recents.is_empty() {
+        submenu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    let clear = MenuItemBuilder::with_id(CLEAR_RECENTS_ID, "Clear Recent")
+        .enabled(!recents.is_empty())
+        .build(app)?;
+    submenu.append(&clear)
+}
+
+/// Brings the "Open Recent" submenu in line with `recents`, if the native
+/// menu is shown.
+pub fn update_recents(app: &AppHandle, recents: &[RecentItem]) {
+    let submenu = app
+        .menu()
+        .and_then(|menu| menu.get(FILE_ID))
+        .and_then(|file| file.as_submenu()?.get(OPEN_RECENT_ID))
+        .and_then(|item| item.as_submenu().cloned());
+    if let Some(submenu) = submenu {
+        if let Err(e) = fill_recents(app, &submenu, recents) {
+            log::warn!("failed to update the Open Recent menu: {e}");
+        }
+    }
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        CHECK_UPDATES_ID => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = updater::show_update_dialog(app).await;
+            });
+        }
+        QUIT_ID => main_window::quit(app),
+        QUIT_WITHOUT_UPDATE_ID => main_window::quit_without_update(app),
+        ABOUT_ID => show_about(app),
+        VIEW_LOGS_ID => reveal_logs(app),
+        DEVTOOLS_ACCESS_ID => devtools::toggle_access(app),
+        ZOOM_IN_ID => zoom::zoom_in(app),
+        ZOOM_OUT_ID => zoom::zoom_out(app),
+        RESET_ZOOM_ID => zoom::reset_zoom(app),
+        CENTER_WINDOW_ID => {
+            if let Err(e) = bounds::center_window(app.clone()) {
+                log::warn!("failed to center the window: {e}");
+            }
+        }
+        CLEAR_RECENTS_ID => {
+            if let Err(e) = recents::clear_recents(app.clone()) {
+                log::warn!("failed to clear the recent projects: {e}");
+            }
+        }
+        id => {
+            if let Some(index) = id
+                .strip_prefix(RECENT_PREFIX)
+                .and_then(|index| index.parse().ok())
+            {
+                recents::open(app, index);
+            }
+        }
+    }
+}
+
+fn show_about(app: &AppHandle) {
+    let info = commands::get_app_version();
+    app.dialog()
+        .message(format!("{} {}", info.name, info.version))
+        .title("About bolt.gives")
+        .show(|_| {});
+}
+
+/// Selects the log file in the file manager, or opens the log folder if
+/// nothing has been logged yet.
+fn reveal_logs(app: &AppHandle) {
+    let Ok(path) = logs::log_file_path(app) else {
+        return;
+    };
+    let result = if path.exists() {
+        app.opener().reveal_item_in_dir(&path)
+    } else if let Some(dir) = path.parent() {
+        app.opener().open_path(dir.to_string_lossy(), None::<&str>)
+    } else {
+        return;
+    };
+    if let Err(e) = result {
+        log::warn!("failed to reveal the log file: {e}");
+    }
+}
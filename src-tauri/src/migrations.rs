@@ -0,0 +1,142 @@
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::persistence::{self, SettingsStore};
+use crate::settings;
+
+/// Schema the current build reads and writes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Key the v0 store used for the window geometry.
+const LEGACY_BOUNDS: &str = "bounds";
+
+type Entries = Map<String, Value>;
+type Step = fn(&mut Entries);
+
+/// Moves `from` to `to` unless `to` is already set.
+fn rename_key(entries: &mut Entries, from: &str, to: &str) {
+    if let Some(value) = entries.remove(from) {
+        entries.entry(to).or_insert(value);
+    }
+}
+
+/// v0 stored the theme as free-form text ("Dark", "LIGHT"); v1 only accepts
+/// the lowercase preference names.
+fn normalize_theme(entries: &mut Entries) {
+    let Some(Value::String(theme)) = entries.get(settings::THEME) else {
+        return;
+    };
+    let normalized = theme.trim().to_lowercase();
+    if matches!(normalized.as_str(), "system" | "light" | "dark") {
+        entries.insert(settings::THEME.to_string(), Value::String(normalized));
+    } else {
+        entries.remove(settings::THEME);
+    }
+}
+
+fn v0_to_v1(entries: &mut Entries) {
+    rename_key(entries, LEGACY_BOUNDS, settings::WINDOW_BOUNDS);
+    normalize_theme(entries);
+}
+
+/// Upgrade steps in order; entry `n` takes the store from schema `n` to `n + 1`.
+const STEPS: &[(&str, Step)] = &[("v0 -> v1: rename bounds, normalize theme", v0_to_v1)];
+
+/// Applies the steps from schema `from` up to `to` and returns the names of
+/// the ones that ran. Every step is safe to re-run.
+fn migrate_entries(entries: &mut Entries, from: u32, to: u32) -> Vec<&'static str> {
+    let steps = STEPS
+        .iter()
+        .skip(from as usize)
+        .take(to.saturating_sub(from) as usize);
+    let mut applied = Vec::new();
+    for (name, migrate) in steps {
+        migrate(entries);
+        applied.push(*name);
+    }
+    entries.insert(settings::SCHEMA_VERSION.to_string(), to.max(from).into());
+    applied
+}
+
+/// Migrates every key in `store` from schema `from` to `to`.
+pub fn migrate_store(store: &dyn SettingsStore, from: u32, to: u32) -> Vec<&'static str> {
+    let mut entries: Entries = store.entries().into_iter().collect();
+    let applied = migrate_entries(&mut entries, from, to);
+    store.clear();
+    for (key, value) in entries {
+        store.set(&key, value);
+    }
+    applied
+}
+
+/// Brings the store on disk up to the current schema.
+///
+/// This runs before the logger is up, so the applied steps are returned for
+/// the caller to log.
+pub fn run(app: &AppHandle) -> Result<Vec<&'static str>, String> {
+    let store = persistence::store(app);
+    let from = store
+        .get(settings::SCHEMA_VERSION)
+        .and_then(|version| version.as_u64())
+        .map_or(0, |version| version as u32);
+    if from == CURRENT_SCHEMA_VERSION {
+        return Ok(Vec::new());
+    }
+
+    let applied = migrate_store(store.as_ref(), from, CURRENT_SCHEMA_VERSION);
+    store.save()?;
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fixture(value: Value) -> Entries {
+        let Value::Object(entries) = value else {
+            unreachable!()
+        };
+        entries
+    }
+
+    #[test]
+    fn migrates_a_v0_store_to_the_current_schema() {
+        let bounds = json!({"x": 10, "y": 20, "width": 800, "height": 600});
+        let mut entries = fixture(json!({
+            "bounds": bounds,
+            "theme": "Dark",
+            "minimize-to-tray": true,
+        }));
+
+        let applied = migrate_entries(&mut entries, 0, CURRENT_SCHEMA_VERSION);
+        assert_eq!(applied.len(), 1);
+        let expected = fixture(json!({
+            "schema-version": CURRENT_SCHEMA_VERSION,
+            "window-bounds": bounds,
+            "theme": "dark",
+            "minimize-to-tray": true,
+        }));
+        assert_eq!(entries, expected);
+
+        // Re-running the same steps leaves the store as it is.
+        migrate_entries(&mut entries, 0, CURRENT_SCHEMA_VERSION);
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn drops_unrecognized_themes() {
+        let mut entries = fixture(json!({"theme": "solarized"}));
+        migrate_entries(&mut entries, 0, CURRENT_SCHEMA_VERSION);
+        assert!(!entries.contains_key(settings::THEME));
+    }
+
+    #[test]
+    fn leaves_newer_schemas_alone() {
+        let mut entries = fixture(json!({"schema-version": 7, "bounds": {}}));
+        assert!(migrate_entries(&mut entries, 7, CURRENT_SCHEMA_VERSION).is_empty());
+        assert!(entries.contains_key(LEGACY_BOUNDS));
+        assert_eq!(entries[settings::SCHEMA_VERSION], json!(7));
+    }
+}
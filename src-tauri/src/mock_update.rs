@@ -0,0 +1,101 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::{settings, version};
+
+/// Size the fake download reports, delivered in [`CHUNKS`] pieces over about
+/// two seconds so progress UI has something to show.
+const DOWNLOAD_SIZE: u64 = 20 * 1024 * 1024;
+const CHUNKS: u64 = 20;
+const CHUNK_DELAY: Duration = Duration::from_millis(100);
+
+/// The update the mock updater offers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MockUpdate {
+    pub version: String,
+    pub notes: Option<String>,
+}
+
+/// The fake update set with [`set_mock_update`], if any; otherwise the next
+/// release is offered.
+#[derive(Default)]
+pub struct MockUpdater(Mutex<Option<MockUpdate>>);
+
+/// The release after `current`: the next patch, or the release a pre-release
+/// leads up to.
+fn next_release(current: &str) -> String {
+    match semver::Version::parse(current) {
+        Ok(version) if version.pre.is_empty() => {
+            format!("{}.{}.{}", version.major, version.minor, version.patch + 1)
+        }
+        Ok(version) => format!("{}.{}.{}", version.major, version.minor, version.patch),
+        Err(_) => format!("{current}-mock"),
+    }
+}
+
+/// Whether update checks and installs are faked: only in debug builds, which
+/// have no signed updates to find, and only with `mock-update` set.
+pub fn is_enabled(app: &AppHandle) -> bool {
+    cfg!(debug_assertions) && settings::get::<bool>(app, settings::MOCK_UPDATE).unwrap_or(false)
+}
+
+/// The update a check finds while the mock updater is on.
+pub fn update(app: &AppHandle) -> Option<MockUpdate> {
+    if !is_enabled(app) {
+        return None;
+    }
+    let configured = app.state::<MockUpdater>().0.lock().unwrap().clone();
+    Some(configured.unwrap_or_else(|| MockUpdate {
+        version: next_release(env!("CARGO_PKG_VERSION")),
+        notes: Some("A mock update for trying out the update flow.".to_string()),
+    }))
+}
+
+/// Pretends to download the update, calling `on_chunk` like
+/// `Update::download` does.
+pub async fn download(mut on_chunk: impl FnMut(usize, Option<u64>)) {
+    let chunk = (DOWNLOAD_SIZE / CHUNKS) as usize;
+    for _ in 0..CHUNKS {
+        tokio::time::sleep(CHUNK_DELAY).await;
+        on_chunk(chunk, Some(DOWNLOAD_SIZE));
+    }
+}
+
+/// Sets the update the mock updater offers. Debug builds only; turn the mock
+/// on with the `mock-update` setting.
+#[tauri::command]
+pub fn set_mock_update(
+    app: AppHandle,
+    version: String,
+    notes: Option<String>,
+) -> Result<(), AppError> {
+    if !cfg!(debug_assertions) {
+        return Err(AppError::Forbidden(
+            "the mock updater is only available in debug builds".to_string(),
+        ));
+    }
+    let version = version.trim().to_string();
+    if version::compare(&version, env!("CARGO_PKG_VERSION"))?.is_le() {
+        return Err(AppError::InvalidInput(format!(
+            "a mock update must be newer than {}",
+            env!("CARGO_PKG_VERSION")
+        )));
+    }
+    *app.state::<MockUpdater>().0.lock().unwrap() = Some(MockUpdate { version, notes });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_the_next_release_by_default() {
+        assert_eq!(next_release("3.0.8"), "3.0.9");
+        assert_eq!(next_release("3.1.0-beta.2"), "3.1.0");
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, Manager, Window};
+use tauri_plugin_notification::{NotificationExt, PermissionState};
+
+use crate::main_window;
+
+const NOTIFICATION_CLICKED_EVENT: &str = "notification-clicked";
+
+/// Set while a notification shown in the background is waiting to be clicked.
+///
+/// The desktop notification backend doesn't report clicks; the OS activates the
+/// app instead, so the first focus of the main window after a background
+/// notification is treated as the click.
+#[derive(Default)]
+pub struct PendingClick(AtomicBool);
+
+fn ensure_permission(app: &AppHandle) -> Result<(), String> {
+    let notification = app.notification();
+    let state = match notification.permission_state().map_err(|e| e.to_string())? {
+        PermissionState::Prompt | PermissionState::PromptWithRationale => notification
+            .request_permission()
+            .map_err(|e| e.to_string())?,
+        state => state,
+    };
+    if state == PermissionState::Granted {
+        Ok(())
+    } else {
+        Err("notification permission was denied; enable notifications for bolt.gives in the system settings".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn notify(
+    app: AppHandle,
+    title: String,
+    body: String,
+    silent: Option<bool>,
+) -> Result<(), String> {
+    ensure_permission(&app)?;
+
+    let mut builder = app.notification().builder().title(title).body(body);
+    if silent.unwrap_or(false) {
+        builder = builder.silent();
+    }
+    builder.show().map_err(|e| e.to_string())?;
+
+    let in_background = app
+        .get_webview_window(main_window::LABEL)
+        .map(|window| !window.is_focused().unwrap_or(false))
+        .unwrap_or(false);
+    if in_background {
+        app.state::<PendingClick>().0.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Tells the frontend a notification brought the main window to the front.
+pub fn on_focus(window: &Window) {
+    if window.label() != main_window::LABEL {
+        return;
+    }
+    let app = window.app_handle();
+    if app.state::<PendingClick>().0.swap(false, Ordering::SeqCst) {
+        main_window::show(app);
+        let _ = app.emit_to(main_window::LABEL, NOTIFICATION_CLICKED_EVENT, ());
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager, Runtime, Webview};
+use tauri_plugin_store::{Store, StoreExt};
+
+use crate::{main_window, settings};
+
+const PERSISTENCE_UNAVAILABLE_EVENT: &str = "persistence-unavailable";
+
+/// The key-value store the settings live in: the store plugin's file, or an
+/// in-memory stand-in when that can't be opened.
+pub trait SettingsStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<Value>;
+    fn set(&self, key: &str, value: Value);
+    /// Removes `key`; whether it was there.
+    fn delete(&self, key: &str) -> bool;
+    fn keys(&self) -> Vec<String>;
+    fn entries(&self) -> Vec<(String, Value)>;
+    fn clear(&self);
+    fn save(&self) -> Result<(), String>;
+    /// Re-reads the store from disk.
+    fn reload(&self) -> Result<(), String>;
+}
+
+impl<R: Runtime> SettingsStore for Store<R> {
+    fn get(&self, key: &str) -> Option<Value> {
+        Store::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) {
+        Store::set(self, key, value);
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        Store::delete(self, key)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        Store::keys(self)
+    }
+
+    fn entries(&self) -> Vec<(String, Value)> {
+        Store::entries(self)
+    }
+
+    fn clear(&self) {
+        Store::clear(self);
+    }
+
+    fn save(&self) -> Result<(), String> {
+        Store::save(self).map_err(|e| e.to_string())
+    }
+
+    fn reload(&self) -> Result<(), String> {
+        Store::reload(self).map_err(|e| e.to_string())
+    }
+}
+
+/// Settings that only last until the app quits.
+#[derive(Default)]
+pub struct MemoryStore(Mutex<Map<String, Value>>);
+
+impl SettingsStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<Value> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: Value) {
+        self.0.lock().unwrap().insert(key.to_string(), value);
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        self.0.lock().unwrap().remove(key).is_some()
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.0.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn entries(&self) -> Vec<(String, Value)> {
+        let entries = self.0.lock().unwrap();
+        entries
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+
+    /// Nothing to write; the settings are lost on quit either way.
+    fn save(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn reload(&self) -> Result<(), String> {
+        Err("settings can't be read from disk this session".to_string())
+    }
+}
+
+/// Whether the settings fell back to memory, and why.
+#[derive(Default)]
+pub struct Persistence {
+    fallback: Arc<MemoryStore>,
+    error: Mutex<Option<String>>,
+    notified: AtomicBool,
+}
+
+impl Persistence {
+    fn is_unavailable(&self) -> bool {
+        self.error.lock().unwrap().is_some()
+    }
+}
+
+/// The store the settings are read from and written to. Once the store file
+/// has failed to open, the in-memory fallback is used for the rest of the
+/// session so reads keep seeing earlier writes.
+pub fn store(app: &AppHandle) -> Arc<dyn SettingsStore> {
+    let persistence = app.state::<Persistence>();
+    if persistence.is_unavailable() {
+        return persistence.fallback.clone();
+    }
+    match app.store(settings::STORE_PATH) {
+        Ok(store) => store,
+        Err(e) => {
+            // This can happen before the logger is up; `init` logs it.
+            *persistence.error.lock().unwrap() = Some(e.to_string());
+            persistence.fallback.clone()
+        }
+    }
+}
+
+/// Logs a failure to open the store once the logger is up.
+pub fn init(app: &AppHandle) {
+    // Opening the store here covers launches where nothing has touched it yet.
+    store(app);
+    if let Some(error) = app.state::<Persistence>().error.lock().unwrap().as_deref() {
+        log::error!("settings can't be opened, so changes won't be saved this session: {error}");
+    }
+}
+
+/// Tells the frontend that settings won't persist, once the main window has
+/// loaded.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let app = webview.app_handle();
+    let persistence = app.state::<Persistence>();
+    let error = persistence.error.lock().unwrap().clone();
+    if let Some(error) = error {
+        if !persistence.notified.swap(true, Ordering::SeqCst) {
+            let _ = app.emit_to(main_window::LABEL, PERSISTENCE_UNAVAILABLE_EVENT, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_store_keeps_values_for_the_session() {
+        let store = MemoryStore::default();
+        store.set("theme", Value::from("dark"));
+        assert_eq!(store.get("theme"), Some(Value::from("dark")));
+        assert_eq!(store.keys(), ["theme"]);
+        assert!(store.delete("theme"));
+        assert!(!store.delete("theme"));
+        assert!(store.entries().is_empty());
+        assert!(store.save().is_ok());
+    }
+}
@@ -0,0 +1,119 @@
+use std::cmp::Ordering;
+
+use tauri::AppHandle;
+
+use crate::{settings, version};
+
+/// One-time work a release needs done to data left by older versions, like
+/// clearing a cache whose format changed. Store schema changes belong in
+/// `migrations`, which also runs on stores restored from a backup.
+pub struct PostUpdateStep {
+    /// The release that introduced the step. It runs when upgrading from an
+    /// older version to this one or later.
+    pub version: &'static str,
+    /// Unique name, recorded in the store once the step has run.
+    pub name: &'static str,
+    pub run: fn(&AppHandle) -> Result<(), String>,
+}
+
+/// Steps in the order they were added.
+const STEPS: &[PostUpdateStep] = &[];
+
+/// Whether `version` is in `(from, to]`.
+fn introduced_between(version: &str, from: &str, to: &str) -> bool {
+    matches!(version::compare(version, from), Ok(Ordering::Greater))
+        && matches!(
+            version::compare(version, to),
+            Ok(Ordering::Less | Ordering::Equal)
+        )
+}
+
+/// The steps an upgrade from `from` to `to` still has to run.
+fn pending<'a>(
+    steps: &'a [PostUpdateStep],
+    done: &[String],
+    from: &str,
+    to: &str,
+) -> Vec<&'a PostUpdateStep> {
+    steps
+        .iter()
+        .filter(|step| introduced_between(step.version, from, to))
+        .filter(|step| !done.iter().any(|name| name == step.name))
+        .collect()
+}
+
+/// Runs the post-update steps when this launch is newer than the last one.
+/// Each step runs once: it is recorded as done even if it fails, since a step
+/// that fails on this data would fail again on every launch.
+///
+/// This has to run before `first_run::init` records the current version.
+pub fn run(app: &AppHandle) {
+    let current = env!("CARGO_PKG_VERSION");
+    // Fresh installs have no old data to fix up.
+    let Some(last) = settings::get::<String>(app, settings::LAST_RUN_VERSION) else {
+        return;
+    };
+    match version::compare(&last, current) {
+        Ok(Ordering::Less) => {}
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("skipping post-update steps: {e}");
+            return;
+        }
+    }
+
+    let mut done: Vec<String> = settings::get(app, settings::POST_UPDATE_STEPS).unwrap_or_default();
+    let steps = pending(STEPS, &done, &last, current);
+    if steps.is_empty() {
+        return;
+    }
+    for step in steps {
+        log::info!("running post-update step {} ({})", step.name, step.version);
+        match (step.run)(app) {
+            Ok(()) => log::info!("post-update step {} done", step.name),
+            Err(e) => log::error!("post-update step {} failed: {e}", step.name),
+        }
+        done.push(step.name.to_string());
+    }
+    if let Err(e) = settings::set(app, settings::POST_UPDATE_STEPS, done) {
+        log::warn!("failed to record the post-update steps: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(version: &'static str, name: &'static str) -> PostUpdateStep {
+        PostUpdateStep {
+            version,
+            name,
+            run: |_| Ok(()),
+        }
+    }
+
+    #[test]
+    fn runs_steps_introduced_since_the_last_version() {
+        let steps = [
+            step("3.0.0", "old"),
+            step("3.1.0", "current"),
+            step("3.2.0", "future"),
+        ];
+        let names = |pending: Vec<&PostUpdateStep>| -> Vec<&str> {
+            pending.iter().map(|step| step.name).collect()
+        };
+        assert_eq!(names(pending(&steps, &[], "3.0.0", "3.1.0")), ["current"]);
+        assert_eq!(
+            names(pending(&steps, &[], "2.9.0", "3.2.0")),
+            ["old", "current", "future"]
+        );
+        assert!(pending(&steps, &[], "3.1.0", "3.1.0").is_empty());
+    }
+
+    #[test]
+    fn skips_steps_already_run() {
+        let steps = [step("3.1.0", "clear-cache")];
+        let done = ["clear-cache".to_string()];
+        assert!(pending(&steps, &done, "3.0.0", "3.1.0").is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::{download, main_window};
+
+/// The last percentage [`report`] showed, so a download only touches the
+/// taskbar when the whole-number percentage changes. `Some(None)` is an
+/// indeterminate bar.
+#[derive(Default)]
+pub struct TaskbarProgress(Mutex<Option<Option<u64>>>);
+
+fn parse_status(state: &str) -> Result<ProgressBarStatus, AppError> {
+    match state {
+        "none" => Ok(ProgressBarStatus::None),
+        "normal" => Ok(ProgressBarStatus::Normal),
+        "indeterminate" => Ok(ProgressBarStatus::Indeterminate),
+        "error" => Ok(ProgressBarStatus::Error),
+        "paused" => Ok(ProgressBarStatus::Paused),
+        _ => Err(AppError::InvalidInput(format!(
+            "unknown progress state \"{state}\", expected none, normal, indeterminate, error or paused"
+        ))),
+    }
+}
+
+/// A percentage as the whole number from 0 to 100 the taskbar expects.
+fn to_percent(progress: f64) -> u64 {
+    if progress.is_nan() {
+        return 0;
+    }
+    progress.clamp(0.0, 100.0).round() as u64
+}
+
+/// Shows `status` and `progress` on the main window's taskbar or dock icon.
+fn apply(app: &AppHandle, status: ProgressBarStatus, progress: Option<u64>) {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    if let Some(window) = app.get_webview_window(main_window::LABEL) {
+        let state = ProgressBarState {
+            status: Some(status),
+            progress,
+        };
+        if let Err(e) = window.set_progress_bar(state) {
+            log::warn!("failed to set the taskbar progress: {e}");
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = (app, status, progress);
+        log::warn!("taskbar progress isn't supported on this platform");
+    }
+}
+
+/// Shows download progress, or an indeterminate bar when the size is unknown.
+pub fn report(app: &AppHandle, percent: Option<f64>) {
+    let percent = percent.map(to_percent);
+    let changed = app
+        .state::<TaskbarProgress>()
+        .0
+        .lock()
+        .unwrap()
+        .replace(percent)
+        != Some(percent);
+    if !changed {
+        return;
+    }
+    match percent {
+        Some(percent) => apply(app, ProgressBarStatus::Normal, Some(percent)),
+        None => apply(app, ProgressBarStatus::Indeterminate, None),
+    }
+}
+
+/// Removes the progress bar once the work is done, whether or not it worked.
+/// The bar is shared, so it stays while another download is running; callers
+/// drop their [`download::ActiveDownload`] first.
+pub fn clear(app: &AppHandle) {
+    if download::in_progress(app) {
+        return;
+    }
+    app.state::<TaskbarProgress>().0.lock().unwrap().take();
+    apply(app, ProgressBarStatus::None, None);
+}
+
+/// Sets the taskbar or dock progress indicator of the main window. `state` is
+/// `none`, `normal`, `indeterminate`, `error` or `paused`; `progress` is a
+/// percentage.
+#[tauri::command]
+pub fn set_progress_bar(
+    app: AppHandle,
+    state: String,
+    progress: Option<f64>,
+) -> Result<(), AppError> {
+    let status = parse_status(&state)?;
+    app.state::<TaskbarProgress>().0.lock().unwrap().take();
+    apply(&app, status, progress.map(to_percent));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_progress_states() {
+        assert!(matches!(
+            parse_status("paused"),
+            Ok(ProgressBarStatus::Paused)
+        ));
+        assert!(matches!(parse_status("none"), Ok(ProgressBarStatus::None)));
+        assert!(parse_status("Normal").is_err());
+    }
+
+    #[test]
+    fn rounds_progress_to_whole_percentages() {
+        assert_eq!(to_percent(42.6), 43);
+        assert_eq!(to_percent(-5.0), 0);
+        assert_eq!(to_percent(250.0), 100);
+        assert_eq!(to_percent(f64::NAN), 0);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A token bucket holding up to `rate` tokens and refilling at `rate` per
+/// second, so bursts of up to one second's worth pass straight through.
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            refilled_at: now,
+        }
+    }
+
+    /// Takes a token, or returns how many milliseconds until one is available.
+    fn take(&mut self, rate: f64, now: Instant) -> Result<(), u64> {
+        // Pick up changes to the configured rate.
+        self.rate = rate;
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - self.tokens) / self.rate * 1000.0).ceil() as u64)
+        }
+    }
+}
+
+/// Per-host token buckets for backend HTTP requests.
+#[derive(Default)]
+pub struct RateLimiter(Mutex<HashMap<String, TokenBucket>>);
+
+impl RateLimiter {
+    /// Takes a token for `host` at `rate` requests per second; `0` doesn't
+    /// limit. On failure, returns the milliseconds until the next token.
+    pub fn check(&self, host: &str, rate: u64) -> Result<(), u64> {
+        if rate == 0 {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let rate = rate as f64;
+        self.0
+            .lock()
+            .unwrap()
+            .entry(host.to_string())
+            .or_insert_with(|| TokenBucket::new(rate, now))
+            .take(rate, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn allows_a_burst_then_limits() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, start);
+        for _ in 0..10 {
+            assert!(bucket.take(10.0, start).is_ok());
+        }
+        assert_eq!(bucket.take(10.0, start), Err(100));
+    }
+
+    #[test]
+    fn refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, start);
+        for _ in 0..10 {
+            bucket.take(10.0, start).unwrap();
+        }
+        let later = start + Duration::from_millis(250);
+        // 2.5 tokens back after 250 ms at 10 per second.
+        assert!(bucket.take(10.0, later).is_ok());
+        assert!(bucket.take(10.0, later).is_ok());
+        assert_eq!(bucket.take(10.0, later), Err(50));
+    }
+
+    #[test]
+    fn never_refills_past_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, start);
+        let much_later = start + Duration::from_secs(60);
+        assert!(bucket.take(2.0, much_later).is_ok());
+        assert!(bucket.take(2.0, much_later).is_ok());
+        assert!(bucket.take(2.0, much_later).is_err());
+    }
+
+    #[test]
+    fn limits_hosts_separately() {
+        let limiter = RateLimiter::default();
+        assert!(limiter.check("a.example", 1).is_ok());
+        assert!(limiter.check("a.example", 1).is_err());
+        assert!(limiter.check("b.example", 1).is_ok());
+        assert!(limiter.check("a.example", 0).is_ok());
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::AppError;
+use crate::{main_window, menu, settings};
+
+/// Most recent projects kept.
+const MAX_RECENTS: usize = 20;
+const MAX_LABEL_LEN: usize = 200;
+
+const OPEN_RECENT_EVENT: &str = "open-recent";
+
+/// A recently opened project as stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredRecent {
+    path: String,
+    label: String,
+    opened_ms: u64,
+}
+
+/// A recently opened project, as returned by [`get_recents`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentItem {
+    pub path: String,
+    pub label: String,
+    pub opened_ms: u64,
+    /// Whether the path was still there when the list was read.
+    pub exists: bool,
+}
+
+/// Puts `recent` first, dropping an older entry for the same path and
+/// anything past [`MAX_RECENTS`].
+fn push(recents: &mut Vec<StoredRecent>, recent: StoredRecent) {
+    recents.retain(|existing| existing.path != recent.path);
+    recents.insert(0, recent);
+    recents.truncate(MAX_RECENTS);
+}
+
+/// The stored list, cut to [`MAX_RECENTS`] in case an import held more.
+fn stored(app: &AppHandle) -> Vec<StoredRecent> {
+    let mut recents: Vec<StoredRecent> = settings::get(app, settings::RECENTS).unwrap_or_default();
+    recents.truncate(MAX_RECENTS);
+    recents
+}
+
+fn store(app: &AppHandle, recents: &[StoredRecent]) -> Result<(), AppError> {
+    if recents.is_empty() {
+        settings::remove(app, settings::RECENTS)
+    } else {
+        settings::set(app, settings::RECENTS, recents)
+    }
+    .map_err(AppError::Store)?;
+    menu::update_recents(app, &list(app));
+    Ok(())
+}
+
+/// The recent projects, most recent first.
+pub fn list(app: &AppHandle) -> Vec<RecentItem> {
+    stored(app)
+        .into_iter()
+        .map(|recent| RecentItem {
+            exists: Path::new(&recent.path).exists(),
+            path: recent.path,
+            label: recent.label,
+            opened_ms: recent.opened_ms,
+        })
+        .collect()
+}
+
+fn record(app: &AppHandle, path: String, label: String) -> Result<(), AppError> {
+    let opened_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let mut recents = stored(app);
+    push(
+        &mut recents,
+        StoredRecent {
+            path,
+            label,
+            opened_ms,
+        },
+    );
+    store(app, &recents)
+}
+
+/// Asks the frontend to open the recent project at `index`, picked from the
+/// native menu, and moves it to the top.
+pub fn open(app: &AppHandle, index: usize) {
+    let Some(recent) = list(app).into_iter().nth(index) else {
+        return;
+    };
+    if let Err(e) = record(app, recent.path.clone(), recent.label.clone()) {
+        log::warn!("failed to update the recent projects: {e}");
+    }
+    main_window::show(app);
+    let _ = app.emit_to(main_window::LABEL, OPEN_RECENT_EVENT, recent);
+}
+
+/// Records `path` as the most recently opened project.
+#[tauri::command]
+pub fn add_recent(app: AppHandle, path: String, label: String) -> Result<(), AppError> {
+    if path.trim().is_empty() || label.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "a recent project needs a path and a label".to_string(),
+        ));
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "recent project labels are limited to {MAX_LABEL_LEN} characters"
+        )));
+    }
+    record(&app, path, label)
+}
+
+/// The last 20 projects opened, most recent first, with the ones whose path
+/// is gone marked as missing.
+#[tauri::command]
+pub fn get_recents(app: AppHandle) -> Vec<RecentItem> {
+    list(&app)
+}
+
+#[tauri::command]
+pub fn clear_recents(app: AppHandle) -> Result<(), AppError> {
+    store(&app, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent(path: &str) -> StoredRecent {
+        StoredRecent {
+            path: path.to_string(),
+            label: path.to_string(),
+            opened_ms: 0,
+        }
+    }
+
+    #[test]
+    fn moves_reopened_projects_to_the_top() {
+        let mut recents = vec![recent("/a"), recent("/b"), recent("/c")];
+        push(&mut recents, recent("/b"));
+        let paths: Vec<&str> = recents.iter().map(|recent| recent.path.as_str()).collect();
+        assert_eq!(paths, ["/b", "/a", "/c"]);
+    }
+
+    #[test]
+    fn keeps_only_the_latest_projects() {
+        let mut recents = Vec::new();
+        for i in 0..MAX_RECENTS + 5 {
+            push(&mut recents, recent(&format!("/{i}")));
+        }
+        assert_eq!(recents.len(), MAX_RECENTS);
+        assert_eq!(recents[0].path, format!("/{}", MAX_RECENTS + 4));
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::{main_window, settings};
+
+const STORE_RECOVERED_EVENT: &str = "store-recovered";
+
+/// Where a corrupt store was moved this launch, until the main window has
+/// been told.
+#[derive(Default)]
+pub struct RecoveredStore(Mutex<Option<String>>);
+
+/// Renames the store file in `dir` to `app-data.corrupt-<millis>.json` if it
+/// isn't a JSON object, and returns the new path.
+fn quarantine(dir: &Path) -> io::Result<Option<PathBuf>> {
+    let path = dir.join(settings::STORE_PATH);
+    let contents = match fs::read(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if matches!(serde_json::from_slice(&contents), Ok(Value::Object(_))) {
+        return Ok(None);
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    let target = dir.join(format!("app-data.corrupt-{millis}.json"));
+    fs::rename(&path, &target)?;
+    Ok(Some(target))
+}
+
+/// Moves a store file that can't be parsed out of the way, so the store
+/// starts from defaults instead of overwriting it on the next save.
+///
+/// This runs before the logger is up, so the moved path is returned for the
+/// caller to log.
+pub fn run(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let moved = quarantine(&dir).map_err(|e| e.to_string())?;
+    if let Some(path) = &moved {
+        *app.state::<RecoveredStore>().0.lock().unwrap() =
+            Some(path.to_string_lossy().into_owned());
+    }
+    Ok(moved)
+}
+
+/// Whether this launch set a corrupt store aside and started from defaults.
+pub fn was_recovered(app: &AppHandle) -> bool {
+    app.state::<RecoveredStore>().0.lock().unwrap().is_some()
+}
+
+/// Tells the main window, once it has loaded, that its settings were reset.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let app = webview.app_handle();
+    let moved = app.state::<RecoveredStore>().0.lock().unwrap().take();
+    if let Some(path) = moved {
+        let _ = app.emit_to(main_window::LABEL, STORE_RECOVERED_EVENT, path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bolt-gives-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn moves_invalid_json_aside() {
+        let dir = scratch_dir("corrupt-store");
+        let store = dir.join(settings::STORE_PATH);
+        fs::write(&store, "{\"theme\": \"dark\",").unwrap();
+
+        let moved = quarantine(&dir).unwrap().unwrap();
+        // With the file gone the store loads empty and every setting falls
+        // back to its default.
+        assert!(!store.exists());
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "{\"theme\": \"dark\",");
+        assert!(moved
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("app-data.corrupt-"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_valid_and_missing_stores() {
+        let dir = scratch_dir("valid-store");
+        assert!(quarantine(&dir).unwrap().is_none());
+
+        let store = dir.join(settings::STORE_PATH);
+        fs::write(&store, "{\"theme\": \"dark\"}").unwrap();
+        assert!(quarantine(&dir).unwrap().is_none());
+        assert!(store.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::{notifications, settings};
+
+/// Most reminders pending at once.
+const MAX_REMINDERS: usize = 50;
+const MAX_TITLE_LEN: usize = 200;
+const MAX_BODY_LEN: usize = 2000;
+
+/// A notification scheduled with `schedule_reminder`, kept in the store until
+/// it fires so it survives a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: u64,
+    pub due_ms: u64,
+    pub title: String,
+    pub body: String,
+}
+
+/// The timers of the pending reminders, by id.
+#[derive(Default)]
+pub struct Reminders(Mutex<HashMap<u64, JoinHandle<()>>>);
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// How long until `due_ms`; overdue reminders fire right away.
+fn delay_until(due_ms: u64, now_ms: u64) -> Duration {
+    Duration::from_millis(due_ms.saturating_sub(now_ms))
+}
+
+/// An id no pending reminder has. Starting from the clock keeps ids from being
+/// reused after earlier reminders fired, even across restarts.
+fn next_id(reminders: &[Reminder], now_ms: u64) -> u64 {
+    let last = reminders
+        .iter()
+        .map(|reminder| reminder.id)
+        .max()
+        .unwrap_or(0);
+    now_ms.max(last + 1)
+}
+
+fn validate(title: &str, body: &str) -> Result<(), AppError> {
+    if title.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "a reminder needs a title".to_string(),
+        ));
+    }
+    if title.chars().count() > MAX_TITLE_LEN || body.chars().count() > MAX_BODY_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "reminder titles are limited to {MAX_TITLE_LEN} characters and bodies to {MAX_BODY_LEN}"
+        )));
+    }
+    Ok(())
+}
+
+/// The stored reminders, cut to [`MAX_REMINDERS`] in case an import held
+/// more.
+fn stored(app: &AppHandle) -> Vec<Reminder> {
+    let mut reminders: Vec<Reminder> = settings::get(app, settings::REMINDERS).unwrap_or_default();
+    reminders.truncate(MAX_REMINDERS);
+    reminders
+}
+
+fn store(app: &AppHandle, reminders: &[Reminder]) -> Result<(), String> {
+    if reminders.is_empty() {
+        settings::remove(app, settings::REMINDERS)
+    } else {
+        settings::set(app, settings::REMINDERS, reminders)
+    }
+}
+
+/// Drops reminder `id` from the store; whether it was there.
+fn forget(app: &AppHandle, id: u64) -> bool {
+    let mut reminders = stored(app);
+    let before = reminders.len();
+    reminders.retain(|reminder| reminder.id != id);
+    if reminders.len() == before {
+        return false;
+    }
+    if let Err(e) = store(app, &reminders) {
+        log::warn!("failed to update the stored reminders: {e}");
+    }
+    true
+}
+
+/// Starts the timer that shows `reminder` when it is due.
+fn arm(app: &AppHandle, reminder: Reminder) {
+    let id = reminder.id;
+    let handle = app.clone();
+    // Held until the timer is recorded, so one that fires at once can't
+    // finish before it is.
+    let state = app.state::<Reminders>();
+    let mut timers = state.0.lock().unwrap();
+    let task = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay_until(reminder.due_ms, now_ms())).await;
+        handle.state::<Reminders>().0.lock().unwrap().remove(&id);
+        forget(&handle, id);
+        if let Err(e) = notifications::notify(handle.clone(), reminder.title, reminder.body, None) {
+            log::warn!("failed to show reminder {id}: {e}");
+        }
+    });
+    timers.insert(id, task);
+}
+
+/// Re-arms the reminders left pending by the previous run. Any that came due
+/// while the app was closed fire right away.
+pub fn init(app: &AppHandle) {
+    let reminders = stored(app);
+    if !reminders.is_empty() {
+        log::info!("re-arming {} reminders", reminders.len());
+    }
+    for reminder in reminders {
+        arm(app, reminder);
+    }
+}
+
+/// Stops every pending timer, for when the stored reminders are wiped.
+pub fn abort_all(app: &AppHandle) {
+    for (_, task) in app.state::<Reminders>().0.lock().unwrap().drain() {
+        task.abort();
+    }
+}
+
+/// Replaces the running timers with ones for the stored reminders, after the
+/// store was changed wholesale.
+pub fn rearm(app: &AppHandle) {
+    abort_all(app);
+    init(app);
+}
+
+/// Shows a notification after `delay_ms` and returns the reminder's id for
+/// [`cancel_reminder`]. Pending reminders are kept across restarts.
+#[tauri::command]
+pub fn schedule_reminder(
+    app: AppHandle,
+    delay_ms: u64,
+    title: String,
+    body: String,
+) -> Result<u64, AppError> {
+    validate(&title, &body)?;
+    let mut reminders = stored(&app);
+    if reminders.len() >= MAX_REMINDERS {
+        return Err(AppError::InvalidInput(format!(
+            "at most {MAX_REMINDERS} reminders can be pending"
+        )));
+    }
+    let now = now_ms();
+    let due_ms = now
+        .checked_add(delay_ms)
+        .ok_or_else(|| AppError::InvalidInput("the reminder delay is too long".to_string()))?;
+    let id = next_id(&reminders, now);
+    let reminder = Reminder {
+        id,
+        due_ms,
+        title,
+        body,
+    };
+    reminders.push(reminder.clone());
+    store(&app, &reminders).map_err(AppError::Store)?;
+    arm(&app, reminder);
+    Ok(id)
+}
+
+/// Cancels a pending reminder.
+#[tauri::command]
+pub fn cancel_reminder(app: AppHandle, id: u64) -> Result<(), AppError> {
+    if let Some(task) = app.state::<Reminders>().0.lock().unwrap().remove(&id) {
+        task.abort();
+    }
+    if forget(&app, id) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "no pending reminder with id {id}"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overdue_reminders_fire_immediately() {
+        assert_eq!(delay_until(1_500, 1_000), Duration::from_millis(500));
+        assert_eq!(delay_until(1_000, 5_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn picks_unused_ids() {
+        let reminder = |id| Reminder {
+            id,
+            due_ms: 0,
+            title: "t".to_string(),
+            body: String::new(),
+        };
+        assert_eq!(next_id(&[], 1_000), 1_000);
+        assert_eq!(next_id(&[reminder(1_000)], 1_000), 1_001);
+        assert_eq!(next_id(&[reminder(5)], 1_000), 1_000);
+    }
+
+    #[test]
+    fn validates_reminder_text() {
+        assert!(validate("Stand up", "").is_ok());
+        assert!(validate("  ", "body").is_err());
+        assert!(validate(&"t".repeat(MAX_TITLE_LEN + 1), "").is_err());
+        assert!(validate("title", &"b".repeat(MAX_BODY_LEN + 1)).is_err());
+    }
+}
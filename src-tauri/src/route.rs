@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::{main_window, safe_mode, settings};
+
+const RESTORE_ROUTE_EVENT: &str = "restore-route";
+const DEFAULT_ROUTE: &str = "/";
+const MAX_ROUTE_LEN: usize = 2048;
+
+/// Set once the stored route has been handed to the frontend, so reloads
+/// don't yank the user back to it.
+#[derive(Default)]
+pub struct RouteRestored(AtomicBool);
+
+/// Returns `route` if it is a same-origin app path that is safe to navigate to.
+pub fn sanitize_route(route: &str) -> Option<&str> {
+    let valid = route.starts_with('/')
+        && !route.starts_with("//")
+        && route.len() <= MAX_ROUTE_LEN
+        && !route.contains('\\')
+        && !route.chars().any(char::is_control);
+    valid.then_some(route)
+}
+
+#[tauri::command]
+pub fn save_last_route(app: AppHandle, route: String) -> Result<(), String> {
+    let route = sanitize_route(&route).ok_or_else(|| format!("invalid route \"{route}\""))?;
+    settings::set(&app, settings::LAST_ROUTE, route)
+}
+
+/// Sends the last saved route to the main window once its first page load finishes.
+pub fn restore(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let app = webview.app_handle();
+    if app.state::<RouteRestored>().0.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let stored =
+        settings::get::<String>(app, settings::LAST_ROUTE).filter(|_| !safe_mode::is_active(app));
+    let route = stored
+        .as_deref()
+        .and_then(sanitize_route)
+        .unwrap_or(DEFAULT_ROUTE);
+    let _ = app.emit_to(main_window::LABEL, RESTORE_ROUTE_EVENT, route);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_app_paths() {
+        assert_eq!(sanitize_route("/"), Some("/"));
+        assert_eq!(
+            sanitize_route("/chat/abc?tab=files#top"),
+            Some("/chat/abc?tab=files#top")
+        );
+    }
+
+    #[test]
+    fn rejects_urls_and_malformed_paths() {
+        assert_eq!(sanitize_route(""), None);
+        assert_eq!(sanitize_route("chat"), None);
+        assert_eq!(sanitize_route("https://example.com"), None);
+        assert_eq!(sanitize_route("//example.com"), None);
+        assert_eq!(sanitize_route("/\\example.com"), None);
+        assert_eq!(sanitize_route("/chat\n"), None);
+        assert_eq!(
+            sanitize_route(&format!("/{}", "a".repeat(MAX_ROUTE_LEN))),
+            None
+        );
+    }
+}
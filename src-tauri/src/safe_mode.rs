@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Emitter, Manager, Webview};
+
+use crate::error::AppError;
+use crate::main_window;
+
+/// Marks the next launch as safe mode. It is a file next to the store rather
+/// than a store key so it still works when the store itself is the problem.
+const SENTINEL: &str = "safe-mode";
+const SAFE_MODE_EVENT: &str = "safe-mode";
+
+/// Whether this launch is in safe mode, and whether the frontend has been
+/// told yet.
+#[derive(Default)]
+pub struct SafeMode {
+    active: AtomicBool,
+    announced: AtomicBool,
+}
+
+fn sentinel_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(dir.join(SENTINEL))
+}
+
+/// Consumes the sentinel left by [`restart_safe_mode`]; returns whether this
+/// launch is in safe mode.
+pub fn detect(app: &AppHandle) -> bool {
+    let Ok(path) = sentinel_path(app) else {
+        return false;
+    };
+    if fs::remove_file(path).is_err() {
+        return false;
+    }
+    app.state::<SafeMode>().active.store(true, Ordering::SeqCst);
+    true
+}
+
+/// While active, the route, theme, zoom, title, window geometry and the
+/// always-on-top, workspace, resizing, title bar and opacity settings are not
+/// restored, so the app comes up in its defaults. Window geometry isn't saved
+/// either, so the bounds from before are still there afterwards.
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<SafeMode>().active.load(Ordering::SeqCst)
+}
+
+/// Emits `safe-mode` once the main window has loaded.
+pub fn flush(webview: &Webview) {
+    if webview.label() != main_window::LABEL {
+        return;
+    }
+    let state = webview.state::<SafeMode>();
+    if state.active.load(Ordering::SeqCst) && !state.announced.swap(true, Ordering::SeqCst) {
+        let _ = webview.emit_to(main_window::LABEL, SAFE_MODE_EVENT, ());
+    }
+}
+
+/// Relaunches the app with settings that could keep it from working left alone.
+#[tauri::command]
+pub fn restart_safe_mode(app: AppHandle) -> Result<(), AppError> {
+    let path = sentinel_path(&app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, [])?;
+    log::warn!("restarting in safe mode");
+    main_window::restart(&app)
+}
+
+#[tauri::command]
+pub fn is_safe_mode(app: AppHandle) -> bool {
+    is_active(&app)
+}
@@ -0,0 +1,251 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::image::Image;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::sync::oneshot;
+
+use crate::error::AppError;
+use crate::{download, main_window};
+
+/// Folder under the app data directory that screenshots go to by default.
+const SCREENSHOTS_DIR: &str = "screenshots";
+
+/// Receives the PNG once the webview has produced it.
+type Done = oneshot::Sender<Result<Vec<u8>, String>>;
+
+fn default_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    Ok(dir
+        .join(SCREENSHOTS_DIR)
+        .join(format!("screenshot-{millis}.png")))
+}
+
+/// Renders the main webview's visible contents to PNG bytes.
+async fn capture(app: &AppHandle) -> Result<Vec<u8>, AppError> {
+    let webview = app
+        .get_webview_window(main_window::LABEL)
+        .ok_or_else(|| AppError::Window("the main window is not open".to_string()))?;
+    let (done, captured) = oneshot::channel();
+    webview
+        .with_webview(move |webview| native::capture(webview, done))
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    captured
+        .await
+        .map_err(|_| AppError::Window("the webview didn't return a screenshot".to_string()))?
+        .map_err(|e| AppError::Window(format!("failed to capture the window: {e}")))
+}
+
+/// Saves a PNG of the main window's contents to `dest`, a `.png` file in a
+/// folder in the app data directory or in the downloads directory, and
+/// returns its path. Without `dest` it goes to the `screenshots` folder in the
+/// app data directory and is also copied to the clipboard.
+#[tauri::command]
+pub async fn capture_window(app: AppHandle, dest: Option<String>) -> Result<String, AppError> {
+    let path = match &dest {
+        Some(dest) => {
+            let is_png = Path::new(dest)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+            if !is_png {
+                return Err(AppError::InvalidInput(format!(
+                    "screenshots are saved as PNG, so \"{dest}\" must end in .png"
+                )));
+            }
+            match download::resolve_dest(Path::new(dest), &download::allowed_roots(&app)) {
+                Some(path) => path,
+                None => {
+                    return Err(AppError::Forbidden(format!(
+                        "screenshots can only be saved in a folder in the app data directory or in the downloads directory, not \"{dest}\""
+                    )))
+                }
+            }
+        }
+        None => default_path(&app)?,
+    };
+    let png = capture(&app).await?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, &png)?;
+    log::info!("saved a screenshot to {}", path.display());
+
+    if dest.is_none() {
+        let copied = Image::from_bytes(&png)
+            .map_err(|e| e.to_string())
+            .and_then(|image| {
+                app.clipboard()
+                    .write_image(&image)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = copied {
+            log::warn!("failed to copy the screenshot to the clipboard: {e}");
+        }
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use tauri::webview::PlatformWebview;
+    use webkit2gtk::{gio, SnapshotOptions, SnapshotRegion, WebViewExt};
+
+    use super::Done;
+
+    fn encode(surface: cairo::Surface) -> Result<Vec<u8>, String> {
+        let image = cairo::ImageSurface::try_from(surface)
+            .map_err(|_| "the snapshot isn't an image".to_string())?;
+        let mut png = Vec::new();
+        image.write_to_png(&mut png).map_err(|e| e.to_string())?;
+        Ok(png)
+    }
+
+    pub fn capture(webview: PlatformWebview, done: Done) {
+        webview.inner().snapshot(
+            SnapshotRegion::Visible,
+            SnapshotOptions::NONE,
+            None::<&gio::Cancellable>,
+            move |result| {
+                let _ = done.send(result.map_err(|e| e.to_string()).and_then(encode));
+            },
+        );
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use ::windows::Win32::System::Com::{IStream, STATFLAG_NONAME, STATSTG, STREAM_SEEK_SET};
+    use ::windows::Win32::UI::Shell::SHCreateMemStream;
+    use tauri::webview::PlatformWebview;
+    use webview2_com::CapturePreviewCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG;
+
+    use super::Done;
+
+    fn read(stream: &IStream) -> Result<Vec<u8>, String> {
+        // SAFETY: `stream` is a live memory stream and the buffer is as long
+        // as the read asks for.
+        unsafe {
+            let mut stat = STATSTG::default();
+            stream
+                .Stat(&mut stat, STATFLAG_NONAME)
+                .map_err(|e| e.to_string())?;
+            stream
+                .Seek(0, STREAM_SEEK_SET, None)
+                .map_err(|e| e.to_string())?;
+            let mut png = vec![0u8; stat.cbSize as usize];
+            let mut read = 0;
+            stream
+                .Read(png.as_mut_ptr().cast(), png.len() as u32, Some(&mut read))
+                .ok()
+                .map_err(|e| e.to_string())?;
+            png.truncate(read as usize);
+            Ok(png)
+        }
+    }
+
+    pub fn capture(webview: PlatformWebview, done: Done) {
+        // SAFETY: `with_webview` runs this on the main thread, which owns the
+        // WebView2 controller.
+        let result = unsafe {
+            let Some(stream) = SHCreateMemStream(None) else {
+                let _ = done.send(Err("failed to create a memory stream".to_string()));
+                return;
+            };
+            let target = stream.clone();
+            let handler = CapturePreviewCompletedHandler::create(Box::new(move |result| {
+                let _ = done.send(
+                    result
+                        .map_err(|e| e.to_string())
+                        .and_then(|()| read(&target)),
+                );
+                Ok(())
+            }));
+            webview.controller().CoreWebView2().and_then(|core| {
+                core.CapturePreview(
+                    COREWEBVIEW2_CAPTURE_PREVIEW_IMAGE_FORMAT_PNG,
+                    &stream,
+                    &handler,
+                )
+            })
+        };
+        // A failure here drops the handler, and with it `done`.
+        if let Err(e) = result {
+            log::warn!("failed to start the capture: {e}");
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::cell::Cell;
+
+    use block2::RcBlock;
+    use objc2::runtime::AnyObject;
+    use objc2_app_kit::{NSBitmapImageFileType, NSBitmapImageRep, NSImage};
+    use objc2_foundation::{NSDictionary, NSError, NSString};
+    use objc2_web_kit::WKWebView;
+    use tauri::webview::PlatformWebview;
+
+    use super::Done;
+
+    fn encode(image: &NSImage) -> Result<Vec<u8>, String> {
+        let tiff = image
+            .TIFFRepresentation()
+            .ok_or_else(|| "the snapshot has no image data".to_string())?;
+        let bitmap = NSBitmapImageRep::imageRepWithData(&tiff)
+            .ok_or_else(|| "the snapshot couldn't be decoded".to_string())?;
+        let properties = NSDictionary::<NSString, AnyObject>::new();
+        // SAFETY: an empty dictionary holds no values of the wrong type.
+        let png = unsafe {
+            bitmap.representationUsingType_properties(NSBitmapImageFileType::PNG, &properties)
+        }
+        .ok_or_else(|| "the snapshot couldn't be encoded as PNG".to_string())?;
+        Ok(png.to_vec())
+    }
+
+    pub fn capture(webview: PlatformWebview, done: Done) {
+        // WebKit may hold on to the block, so it only answers once.
+        let done = Cell::new(Some(done));
+        let handler = RcBlock::new(move |image: *mut NSImage, error: *mut NSError| {
+            let Some(done) = done.take() else {
+                return;
+            };
+            // SAFETY: WebKit passes either a live image or a live error.
+            let result = match unsafe { (image.as_ref(), error.as_ref()) } {
+                (Some(image), _) => encode(image),
+                (None, Some(error)) => Err(error.localizedDescription().to_string()),
+                (None, None) => Err("WebKit returned no snapshot".to_string()),
+            };
+            let _ = done.send(result);
+        });
+        // SAFETY: tauri hands out the WKWebView backing this live webview, and
+        // `with_webview` runs on the main thread WebKit requires.
+        unsafe {
+            let webview = &*webview.inner().cast::<WKWebView>();
+            webview.takeSnapshotWithConfiguration_completionHandler(None, &handler);
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+mod native {
+    use tauri::webview::PlatformWebview;
+
+    use super::Done;
+
+    pub fn capture(_webview: PlatformWebview, done: Done) {
+        let _ = done.send(Err(
+            "screenshots aren't supported on this platform".to_string()
+        ));
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::OnceLock;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use tauri::{AppHandle, Emitter};
+
+use crate::{main_window, settings};
+
+const KEYRING_SERVICE: &str = "bolt.gives";
+const KEYRING_USER: &str = "store-encryption-key";
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const OBFUSCATED_PREFIX: &str = "obf:v1:";
+const NONCE_LEN: usize = 12;
+
+const INSECURE_EVENT: &str = "secret-storage-insecure";
+
+/// Store keys whose values are only ever written encrypted.
+const SECRET_KEYS: &[&str] = &[settings::AUTH_TOKEN];
+
+/// The store encryption key, loaded from (or created in) the OS keychain on
+/// first use. `None` when no keychain is available this session.
+static KEY: OnceLock<Option<Key>> = OnceLock::new();
+
+fn load_or_create_key() -> Result<Key, keyring::Error> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes = BASE64
+                .decode(encoded)
+                .map_err(|e| keyring::Error::BadEncoding(e.to_string().into_bytes()))?;
+            if bytes.len() != 32 {
+                return Err(keyring::Error::BadEncoding(bytes));
+            }
+            Ok(*Key::from_slice(&bytes))
+        }
+        Err(keyring::Error::NoEntry) => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            entry.set_password(&BASE64.encode(key))?;
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn key() -> Option<&'static Key> {
+    KEY.get_or_init(|| match load_or_create_key() {
+        Ok(key) => Some(key),
+        Err(e) => {
+            log::warn!("OS keychain unavailable, secrets are only obfuscated: {e}");
+            None
+        }
+    })
+    .as_ref()
+}
+
+fn encrypt(key: &Key, plaintext: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = ChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| e.to_string())?;
+    let mut blob = nonce.to_vec();
+    blob.extend_from_slice(&ciphertext);
+    Ok(format!("{ENCRYPTED_PREFIX}{}", BASE64.encode(blob)))
+}
+
+fn decrypt(key: &Key, stored: &str) -> Result<String, String> {
+    let encoded = stored
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or("secret is not encrypted")?;
+    let blob = BASE64.decode(encoded).map_err(|e| e.to_string())?;
+    if blob.len() < NONCE_LEN {
+        return Err("encrypted secret is truncated".to_string());
+    }
+    let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+    let plaintext = ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "secret could not be decrypted".to_string())?;
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+/// Fallback encoding when there is no keychain. It only keeps the value from
+/// being readable at a glance; it is not encryption.
+fn obfuscate(plaintext: &str) -> String {
+    format!("{OBFUSCATED_PREFIX}{}", BASE64.encode(plaintext))
+}
+
+fn deobfuscate(stored: &str) -> Result<String, String> {
+    let encoded = stored
+        .strip_prefix(OBFUSCATED_PREFIX)
+        .ok_or("secret is not obfuscated")?;
+    let bytes = BASE64.decode(encoded).map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+fn ensure_secret_key(key: &str) -> Result<(), String> {
+    if SECRET_KEYS.contains(&key) {
+        Ok(())
+    } else {
+        Err(format!("\"{key}\" is not a secret setting"))
+    }
+}
+
+/// Encrypts `value` and writes it to the store under `key`.
+pub fn set(app: &AppHandle, key: &str, value: &str) -> Result<(), String> {
+    ensure_secret_key(key)?;
+    let stored = match self::key() {
+        Some(encryption_key) => encrypt(encryption_key, value)?,
+        None => {
+            let _ = app.emit_to(main_window::LABEL, INSECURE_EVENT, ());
+            obfuscate(value)
+        }
+    };
+    settings::set(app, key, stored)
+}
+
+/// Reads and decrypts the value stored under `key`.
+pub fn get(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    ensure_secret_key(key)?;
+    let Some(stored) = settings::get::<String>(app, key) else {
+        return Ok(None);
+    };
+    if stored.starts_with(OBFUSCATED_PREFIX) {
+        return deobfuscate(&stored).map(Some);
+    }
+    let encryption_key = self::key().ok_or("the OS keychain is unavailable")?;
+    decrypt(encryption_key, &stored).map(Some)
+}
+
+#[tauri::command]
+pub fn set_secret(app: AppHandle, key: String, value: String) -> Result<(), String> {
+    set(&app, &key, &value)?;
+    settings::broadcast(&app, &key, serde_json::Value::Null);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_secret(app: AppHandle, key: String) -> Result<Option<String>, String> {
+    get(&app, &key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encryption_round_trips() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        let stored = encrypt(&key, "token-123").unwrap();
+        assert!(stored.starts_with(ENCRYPTED_PREFIX));
+        assert!(!stored.contains("token-123"));
+        assert_eq!(decrypt(&key, &stored).unwrap(), "token-123");
+
+        let other = ChaCha20Poly1305::generate_key(&mut OsRng);
+        assert!(decrypt(&other, &stored).is_err());
+    }
+
+    #[test]
+    fn obfuscation_round_trips() {
+        let stored = obfuscate("token-123");
+        assert!(!stored.contains("token-123"));
+        assert_eq!(deobfuscate(&stored).unwrap(), "token-123");
+    }
+}
@@ -0,0 +1,159 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::{main_window, settings, zoom};
+
+const DEFAULT_TOGGLE_SHORTCUT: &str = "CommandOrControl+Shift+B";
+const RELOAD_SHORTCUT: &str = "CommandOrControl+R";
+const ZOOM_IN_SHORTCUT: &str = "CommandOrControl+Equal";
+const ZOOM_OUT_SHORTCUT: &str = "CommandOrControl+Minus";
+const RESET_ZOOM_SHORTCUT: &str = "CommandOrControl+Digit0";
+/// The View menu's zoom accelerators, for when there is no native menu.
+const ZOOM_SHORTCUTS: [&str; 3] = [ZOOM_IN_SHORTCUT, ZOOM_OUT_SHORTCUT, RESET_ZOOM_SHORTCUT];
+
+pub fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator
+        .parse()
+        .map_err(|e| format!("invalid shortcut \"{accelerator}\": {e}"))
+}
+
+/// The toggle shortcut from the store, or the default if unset or invalid.
+pub fn toggle_shortcut(app: &AppHandle) -> Shortcut {
+    settings::get::<String>(app, settings::TOGGLE_SHORTCUT)
+        .and_then(|accelerator| parse(&accelerator).ok())
+        .unwrap_or_else(|| parse(DEFAULT_TOGGLE_SHORTCUT).expect("default shortcut is valid"))
+}
+
+fn register_toggle(app: &AppHandle, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                main_window::toggle(app);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Registers the show/hide shortcut. Failing to grab it (e.g. another app owns
+/// the combination) is logged rather than aborting startup.
+pub fn init(app: &AppHandle) {
+    let shortcut = toggle_shortcut(app);
+    if let Err(e) = register_toggle(app, shortcut) {
+        log::warn!("failed to register the toggle shortcut: {e}");
+    }
+}
+
+/// Moves the show/hide shortcut from `previous` to the stored one, after the
+/// store was changed wholesale.
+pub fn rearm_toggle(app: &AppHandle, previous: Shortcut) {
+    let shortcut = toggle_shortcut(app);
+    if shortcut == previous {
+        return;
+    }
+    let global_shortcut = app.global_shortcut();
+    if global_shortcut.is_registered(previous) {
+        let _ = global_shortcut.unregister(previous);
+    }
+    if let Err(e) = register_toggle(app, shortcut) {
+        log::warn!("failed to register the toggle shortcut: {e}");
+    }
+}
+
+/// Whether CmdOrCtrl+R reloads the frontend: on in debug builds, and in
+/// release only once `reload-shortcut-enabled` is set.
+fn reload_enabled(app: &AppHandle) -> bool {
+    settings::get::<bool>(app, settings::RELOAD_SHORTCUT_ENABLED).unwrap_or(cfg!(debug_assertions))
+}
+
+/// Holds the reload shortcut only while the main window is focused, so the
+/// combination still reaches other apps the rest of the time.
+pub fn on_focus_changed(app: &AppHandle, focused: bool) {
+    let shortcut = parse(RELOAD_SHORTCUT).expect("reload shortcut is valid");
+    let global_shortcut = app.global_shortcut();
+    if !focused || !reload_enabled(app) {
+        if global_shortcut.is_registered(shortcut) {
+            let _ = global_shortcut.unregister(shortcut);
+        }
+        return;
+    }
+    if global_shortcut.is_registered(shortcut) {
+        return;
+    }
+    let result = global_shortcut.on_shortcut(shortcut, |app, _, event| {
+        if event.state == ShortcutState::Pressed {
+            let _ = main_window::reload(app);
+        }
+    });
+    if let Err(e) = result {
+        log::warn!("failed to register the reload shortcut: {e}");
+    }
+}
+
+/// Holds the zoom shortcuts while one of the app's windows is focused, if the
+/// native menu that normally carries them isn't installed (`show-native-menu`
+/// off on Windows and Linux). `focused` is the state the window that changed
+/// focus reported, since it may not show up in `is_focused` yet.
+pub fn on_zoom_focus_changed(app: &AppHandle, focused: bool) {
+    let wanted = app.menu().is_none()
+        && (focused
+            || app
+                .webview_windows()
+                .values()
+                .any(|window| window.is_focused().unwrap_or(false)));
+    let global_shortcut = app.global_shortcut();
+    for accelerator in ZOOM_SHORTCUTS {
+        let shortcut = parse(accelerator).expect("zoom shortcuts are valid");
+        let registered = global_shortcut.is_registered(shortcut);
+        if wanted && !registered {
+            let result = global_shortcut.on_shortcut(shortcut, move |app, _, event| {
+                if event.state != ShortcutState::Pressed {
+                    return;
+                }
+                match accelerator {
+                    ZOOM_IN_SHORTCUT => zoom::zoom_in(app),
+                    ZOOM_OUT_SHORTCUT => zoom::zoom_out(app),
+                    _ => zoom::reset_zoom(app),
+                }
+            });
+            if let Err(e) = result {
+                log::warn!("failed to register the {accelerator} zoom shortcut: {e}");
+            }
+        } else if !wanted && registered {
+            let _ = global_shortcut.unregister(shortcut);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn set_toggle_shortcut(app: AppHandle, accelerator: String) -> Result<(), String> {
+    let shortcut = parse(&accelerator)?;
+    let previous = toggle_shortcut(&app);
+    if shortcut != previous {
+        let global_shortcut = app.global_shortcut();
+        if global_shortcut.is_registered(previous) {
+            global_shortcut
+                .unregister(previous)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Err(e) = register_toggle(&app, shortcut) {
+            let _ = register_toggle(&app, previous);
+            return Err(e);
+        }
+    }
+    settings::update(&app, settings::TOGGLE_SHORTCUT, accelerator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_shortcuts_parse() {
+        for accelerator in [DEFAULT_TOGGLE_SHORTCUT, RELOAD_SHORTCUT]
+            .into_iter()
+            .chain(ZOOM_SHORTCUTS)
+        {
+            assert!(parse(accelerator).is_ok(), "{accelerator}");
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::settings;
+
+/// Broadcasts that the app is shutting down to long-running background tasks.
+pub struct Shutdown(watch::Sender<bool>);
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self(watch::channel(false).0)
+    }
+}
+
+/// A receiver for background loops to pass to [`requested`].
+pub fn subscribe(app: &AppHandle) -> watch::Receiver<bool> {
+    app.state::<Shutdown>().0.subscribe()
+}
+
+/// Resolves once shutdown has been signalled.
+pub async fn requested(receiver: &mut watch::Receiver<bool>) {
+    // An error means the sender is gone, which only happens at teardown.
+    let _ = receiver.wait_for(|stopping| *stopping).await;
+}
+
+/// Stops background tasks and writes pending store changes to disk. Safe to
+/// call more than once.
+pub fn shutdown(app: &AppHandle) {
+    if !app.state::<Shutdown>().0.send_replace(true) {
+        log::info!("shutting down");
+    }
+    if let Err(e) = settings::flush(app) {
+        log::warn!("failed to save settings on shutdown: {e}");
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::{bounds, main_window, safe_mode, window_state};
+
+/// Label of the splash window declared in `tauri.conf.json`.
+pub const LABEL: &str = "splashscreen";
+
+/// Shows the main window anyway if the frontend never reports that it's ready.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set once the main window has replaced the splash.
+#[derive(Default)]
+pub struct MainRevealed(AtomicBool);
+
+/// Whether the main window has been shown yet.
+pub fn is_revealed(app: &AppHandle) -> bool {
+    app.state::<MainRevealed>().0.load(Ordering::SeqCst)
+}
+
+/// Shows the splash window while the main window loads hidden.
+///
+/// With `hidden` (a `--minimized` launch) neither is shown: the main window is
+/// prepared at its restored bounds and stays hidden until the user opens it
+/// from the tray, a deep link or a second launch.
+pub fn start(app: &AppHandle, hidden: bool) {
+    if hidden {
+        if let Some(splash) = app.get_webview_window(LABEL) {
+            let _ = splash.close();
+        }
+        reveal_main(app, false);
+        return;
+    }
+    let Some(splash) = app.get_webview_window(LABEL) else {
+        reveal_main(app, true);
+        return;
+    };
+    let _ = splash.show();
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(READY_TIMEOUT).await;
+        if !is_revealed(&handle) {
+            log::warn!("frontend didn't signal ready in time, showing the main window");
+            reveal_main(&handle, true);
+        }
+    });
+}
+
+/// Closes the splash and restores the main window's bounds, showing it if
+/// `show` is set. Only the first call does anything.
+fn reveal_main(app: &AppHandle, show: bool) {
+    if app.state::<MainRevealed>().0.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(splash) = app.get_webview_window(LABEL) {
+        let _ = splash.close();
+    }
+    if let Some(window) = app.get_webview_window(main_window::LABEL) {
+        if !safe_mode::is_active(app) {
+            let _ = bounds::restore(&window.as_ref().window());
+            window_state::restore(&window.as_ref().window());
+        }
+        if !show {
+            return;
+        }
+        let _ = window.show();
+        let _ = window.set_focus();
+
+        #[cfg(debug_assertions)]
+        window.open_devtools();
+    }
+}
+
+/// Replaces the splash with the main window now, without waiting for the
+/// frontend, for when something asks for the main window early.
+pub fn reveal(app: &AppHandle) {
+    reveal_main(app, true);
+}
+
+#[tauri::command]
+pub fn close_splashscreen(app: AppHandle) {
+    reveal_main(&app, true);
+}
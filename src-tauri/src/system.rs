@@ -0,0 +1,42 @@
+use serde::Serialize;
+use sysinfo::System;
+
+use crate::error::AppError;
+
+/// The OS and hardware the app is running on. Fields the OS doesn't report
+/// are `null`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub os: &'static str,
+    pub os_version: Option<String>,
+    pub arch: &'static str,
+    /// Installed RAM in bytes.
+    pub total_memory: Option<u64>,
+    pub cpu_count: Option<usize>,
+    /// BCP 47 tag of the user's preferred locale, e.g. `en-US`.
+    pub locale: Option<String>,
+    pub hostname: Option<String>,
+}
+
+/// Gathers [`SystemInfo`]. Some of it means reading `/proc` or calling into
+/// the OS, so keep it off the main thread.
+pub fn collect() -> SystemInfo {
+    let mut system = System::new();
+    system.refresh_memory();
+    SystemInfo {
+        os: std::env::consts::OS,
+        os_version: System::long_os_version().or_else(System::os_version),
+        arch: std::env::consts::ARCH,
+        total_memory: Some(system.total_memory()).filter(|bytes| *bytes > 0),
+        cpu_count: std::thread::available_parallelism().ok().map(usize::from),
+        locale: sys_locale::get_locale(),
+        hostname: System::host_name(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_system_info() -> Result<SystemInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(collect)
+        .await
+        .map_err(|e| AppError::Io(e.to_string()))
+}
@@ -0,0 +1,65 @@
+use std::future::Future;
+use std::sync::Mutex;
+
+use tauri::async_runtime::{self, JoinHandle};
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+use crate::{backup, devtools, idle_install, lock, telemetry, updater};
+
+/// Handles to the long-running background tasks, so they can be aborted and
+/// started again without restarting the app.
+#[derive(Default)]
+pub struct BackgroundTasks(Mutex<Vec<(&'static str, JoinHandle<()>)>>);
+
+fn spawn<F>(app: &AppHandle, name: &'static str, task: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    log::info!("starting background task {name}");
+    let handle = async_runtime::spawn(async move {
+        task.await;
+        log::info!("background task {name} stopped");
+    });
+    app.state::<BackgroundTasks>()
+        .0
+        .lock()
+        .unwrap()
+        .push((name, handle));
+}
+
+/// Starts the backup, telemetry, auto-lock and (in release builds) update
+/// check and idle install loops. Needs [`updater::CheckSchedule`] to be managed already.
+pub fn spawn_all(app: &AppHandle) {
+    spawn(app, "backup", backup::run(app.clone()));
+    spawn(app, "telemetry", telemetry::run(app.clone()));
+    spawn(app, "auto-lock", lock::run(app.clone()));
+    if !cfg!(debug_assertions) {
+        let hours = app.state::<updater::CheckSchedule>().subscribe();
+        spawn(
+            app,
+            "update-check",
+            updater::check_update(app.clone(), hours),
+        );
+        spawn(app, "idle-install", idle_install::run(app.clone()));
+    }
+}
+
+fn abort_all(app: &AppHandle) {
+    let tasks = std::mem::take(&mut *app.state::<BackgroundTasks>().0.lock().unwrap());
+    for (name, handle) in tasks {
+        log::info!("aborting background task {name}");
+        handle.abort();
+    }
+}
+
+/// Aborts every background task and starts them again, for recovering from a
+/// wedged loop. Gated like devtools.
+#[tauri::command]
+pub fn restart_background_tasks(app: AppHandle) -> Result<(), AppError> {
+    devtools::ensure_enabled(&app)?;
+    log::warn!("restarting background tasks");
+    abort_all(&app);
+    spawn_all(&app);
+    Ok(())
+}
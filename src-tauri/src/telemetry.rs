@@ -0,0 +1,270 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_http::reqwest::header;
+use url::Url;
+
+use crate::error::AppError;
+use crate::{http, settings, shutdown};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+/// How long the final flush may hold up quitting.
+const EXIT_FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events kept while the endpoint is unreachable; the oldest are dropped first.
+const MAX_QUEUED: usize = 500;
+const MAX_NAME_LEN: usize = 64;
+const MAX_PROPS: usize = 20;
+const MAX_PROP_LEN: usize = 200;
+
+/// Endpoint baked in at build time; the `telemetry-endpoint` setting overrides it.
+const DEFAULT_ENDPOINT: Option<&str> = option_env!("BOLT_TELEMETRY_ENDPOINT");
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Event {
+    pub name: String,
+    pub props: Map<String, Value>,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Batch<'a> {
+    install_id: &'a str,
+    app_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    events: &'a [Event],
+}
+
+/// Events waiting for the next flush.
+#[derive(Default)]
+pub struct TelemetryQueue(Mutex<Vec<Event>>);
+
+/// `DO_NOT_TRACK` set to anything but `0` turns telemetry off regardless of
+/// the stored consent.
+fn do_not_track() -> bool {
+    std::env::var("DO_NOT_TRACK").is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+fn is_enabled(app: &AppHandle) -> bool {
+    !do_not_track() && settings::get::<bool>(app, settings::TELEMETRY_ENABLED).unwrap_or(false)
+}
+
+fn endpoint(app: &AppHandle) -> Option<Url> {
+    settings::get::<String>(app, settings::TELEMETRY_ENDPOINT)
+        .or_else(|| DEFAULT_ENDPOINT.map(str::to_string))
+        .and_then(|url| Url::parse(&url).ok())
+        .filter(|url| url.scheme() == "https")
+}
+
+/// A random ID for this installation, created on first use. It identifies
+/// nothing beyond "the same install".
+fn install_id(app: &AppHandle) -> Result<String, String> {
+    if let Some(id) = settings::get::<String>(app, settings::INSTALL_ID) {
+        return Ok(id);
+    }
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    let id: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    settings::set(app, settings::INSTALL_ID, &id)?;
+    Ok(id)
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "event name \"{name}\" must be 1-{MAX_NAME_LEN} lowercase letters, digits, '_', '.' or '-'"
+        )))
+    }
+}
+
+/// Keeps only flat, short scalar properties. Nested values and long strings
+/// are where free-form user content (paths, prompts, emails) would end up, so
+/// they are dropped rather than sent.
+fn sanitize_props(props: Value) -> Map<String, Value> {
+    let Value::Object(props) = props else {
+        return Map::new();
+    };
+    props
+        .into_iter()
+        .filter(|(key, value)| {
+            key.len() <= MAX_NAME_LEN
+                && match value {
+                    Value::Bool(_) | Value::Number(_) => true,
+                    Value::String(text) => text.chars().count() <= MAX_PROP_LEN,
+                    _ => false,
+                }
+        })
+        .take(MAX_PROPS)
+        .collect()
+}
+
+fn enqueue(queue: &mut Vec<Event>, event: Event) {
+    if queue.len() >= MAX_QUEUED {
+        queue.remove(0);
+    }
+    queue.push(event);
+}
+
+/// Sends the queued events in one request. They are put back if it fails so
+/// the next flush retries them.
+async fn flush(app: &AppHandle) {
+    let events = std::mem::take(&mut *app.state::<TelemetryQueue>().0.lock().unwrap());
+    if events.is_empty() || !is_enabled(app) {
+        return;
+    }
+    let Some(endpoint) = endpoint(app) else {
+        log::debug!(
+            "no telemetry endpoint configured, dropping {} events",
+            events.len()
+        );
+        return;
+    };
+
+    if let Err(e) = send(app, &endpoint, &events).await {
+        log::debug!("failed to send telemetry: {e}");
+        let state = app.state::<TelemetryQueue>();
+        let mut queue = state.0.lock().unwrap();
+        let newer = std::mem::replace(&mut *queue, events);
+        for event in newer {
+            enqueue(&mut queue, event);
+        }
+    }
+}
+
+async fn send(app: &AppHandle, endpoint: &Url, events: &[Event]) -> Result<(), String> {
+    let install_id = install_id(app)?;
+    let body = serde_json::to_vec(&Batch {
+        install_id: &install_id,
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        events,
+    })
+    .map_err(|e| e.to_string())?;
+    http::client(app, REQUEST_TIMEOUT)?
+        .post(endpoint.clone())
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Flushes queued events every [`FLUSH_INTERVAL`] until shutdown.
+pub async fn run(app: AppHandle) {
+    let mut stop = shutdown::subscribe(&app);
+    let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => flush(&app).await,
+            _ = shutdown::requested(&mut stop) => return,
+        }
+    }
+}
+
+/// Sends what is left on exit, without holding up quitting for long.
+pub fn flush_on_exit(app: &AppHandle) {
+    tauri::async_runtime::block_on(async {
+        let _ = tokio::time::timeout(EXIT_FLUSH_TIMEOUT, flush(app)).await;
+    });
+}
+
+/// Records consent; turning telemetry off also discards anything queued.
+#[tauri::command]
+pub fn set_telemetry_enabled(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    settings::update(&app, settings::TELEMETRY_ENABLED, enabled).map_err(AppError::Store)?;
+    if !enabled {
+        app.state::<TelemetryQueue>().0.lock().unwrap().clear();
+    }
+    Ok(())
+}
+
+/// Whether events are being collected: consent was given and `DO_NOT_TRACK`
+/// isn't set.
+#[tauri::command]
+pub fn is_telemetry_enabled(app: AppHandle) -> bool {
+    is_enabled(&app)
+}
+
+/// Queues an event for the next batch. Does nothing unless telemetry is enabled.
+#[tauri::command]
+pub fn track_event(app: AppHandle, name: String, props: Option<Value>) -> Result<(), AppError> {
+    if !is_enabled(&app) {
+        return Ok(());
+    }
+    validate_name(&name)?;
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+    let event = Event {
+        name,
+        props: sanitize_props(props.unwrap_or(Value::Null)),
+        timestamp_ms,
+    };
+    enqueue(&mut app.state::<TelemetryQueue>().0.lock().unwrap(), event);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn keeps_only_short_scalar_props() {
+        let props = sanitize_props(json!({
+            "tab": "files",
+            "count": 3,
+            "ok": true,
+            "path": "x".repeat(MAX_PROP_LEN + 1),
+            "nested": {"email": "someone@example.com"},
+            "list": [1, 2],
+            "none": null,
+        }));
+        assert_eq!(
+            Value::Object(props),
+            json!({"tab": "files", "count": 3, "ok": true})
+        );
+        assert!(sanitize_props(json!("not an object")).is_empty());
+    }
+
+    #[test]
+    fn validates_event_names() {
+        assert!(validate_name("chat.message_sent").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("Has Spaces").is_err());
+        assert!(validate_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn drops_the_oldest_events_when_full() {
+        let event = |n: u64| Event {
+            name: "e".to_string(),
+            props: Map::new(),
+            timestamp_ms: n,
+        };
+        let mut queue: Vec<Event> = (0..MAX_QUEUED as u64).map(event).collect();
+        enqueue(&mut queue, event(MAX_QUEUED as u64));
+        assert_eq!(queue.len(), MAX_QUEUED);
+        assert_eq!(queue[0].timestamp_ms, 1);
+        assert_eq!(queue.last().unwrap().timestamp_ms, MAX_QUEUED as u64);
+    }
+}
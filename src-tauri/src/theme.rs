@@ -0,0 +1,164 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tauri::window::Color;
+use tauri::{AppHandle, Emitter, Manager, Theme, WebviewWindow, Window};
+
+use crate::error::AppError;
+use crate::{main_window, settings};
+
+const THEME_CHANGED_EVENT: &str = "theme-changed";
+
+/// Backgrounds painted before the frontend's own, matching its dark and light themes.
+const DARK_BACKGROUND: Color = Color(0x0a, 0x0a, 0x0a, 0xff);
+const LIGHT_BACKGROUND: Color = Color(0xff, 0xff, 0xff, 0xff);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemePreference {
+    fn window_theme(self) -> Option<Theme> {
+        match self {
+            Self::System => None,
+            Self::Light => Some(Theme::Light),
+            Self::Dark => Some(Theme::Dark),
+        }
+    }
+}
+
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+pub fn preference(app: &AppHandle) -> ThemePreference {
+    settings::get(app, settings::THEME).unwrap_or_default()
+}
+
+/// Applies the stored theme preference to `window`.
+pub fn apply(window: &Window) -> Result<(), String> {
+    let preference = preference(window.app_handle());
+    window
+        .set_theme(preference.window_theme())
+        .map_err(|e| e.to_string())
+}
+
+/// Parses `#rgb`, `#rrggbb` or `#rrggbbaa`.
+pub fn parse_color(color: &str) -> Result<Color, AppError> {
+    let invalid = || AppError::InvalidInput(format!("\"{color}\" is not a hex color like #1a1a1a"));
+    let digits = color.strip_prefix('#').ok_or_else(invalid)?;
+    if !matches!(digits.len(), 3 | 6 | 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    Color::from_str(color).map_err(|_| invalid())
+}
+
+/// The stored background color, or one matching the theme `window` is shown in.
+fn background_color(window: &WebviewWindow) -> Color {
+    if let Some(color) =
+        settings::get::<String>(window.app_handle(), settings::WINDOW_BACKGROUND_COLOR)
+            .and_then(|color| parse_color(&color).ok())
+    {
+        return color;
+    }
+    let theme = preference(window.app_handle())
+        .window_theme()
+        .or_else(|| window.theme().ok());
+    match theme {
+        Some(Theme::Dark) => DARK_BACKGROUND,
+        _ => LIGHT_BACKGROUND,
+    }
+}
+
+/// Paints `window` in its background color, so it doesn't flash white before
+/// the frontend has rendered.
+pub fn apply_background(window: &WebviewWindow) -> Result<(), String> {
+    window
+        .set_background_color(Some(background_color(window)))
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the color windows show before the frontend paints; `None` goes back
+/// to following the theme.
+#[tauri::command]
+pub fn set_window_background_color(app: AppHandle, color: Option<String>) -> Result<(), AppError> {
+    match color
+        .as_deref()
+        .map(str::trim)
+        .filter(|color| !color.is_empty())
+    {
+        Some(color) => {
+            parse_color(color)?;
+            settings::update(&app, settings::WINDOW_BACKGROUND_COLOR, color)
+        }
+        None => settings::clear(&app, settings::WINDOW_BACKGROUND_COLOR),
+    }
+    .map_err(AppError::Store)?;
+    apply_all(&app).map_err(AppError::Window)
+}
+
+#[tauri::command]
+pub fn set_theme(app: AppHandle, theme: String) -> Result<(), String> {
+    let preference = match theme.as_str() {
+        "system" => ThemePreference::System,
+        "light" => ThemePreference::Light,
+        "dark" => ThemePreference::Dark,
+        _ => return Err(format!("unknown theme \"{theme}\"")),
+    };
+    settings::update(&app, settings::THEME, preference)?;
+    apply_all(&app)
+}
+
+/// Applies the stored theme preference to every open window.
+pub fn apply_all(app: &AppHandle) -> Result<(), String> {
+    let theme = preference(app).window_theme();
+    for window in app.webview_windows().values() {
+        window.set_theme(theme).map_err(|e| e.to_string())?;
+        apply_background(window)?;
+    }
+    Ok(())
+}
+
+/// Forwards OS theme flips to the frontend while the user follows the system theme.
+pub fn on_theme_changed(window: &Window, theme: Theme) {
+    if window.label() != main_window::LABEL {
+        return;
+    }
+    let app = window.app_handle();
+    if preference(app) == ThemePreference::System {
+        let _ = app.emit_to(main_window::LABEL, THEME_CHANGED_EVENT, theme_name(theme));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#fff").unwrap(), Color(255, 255, 255, 255));
+        assert_eq!(
+            parse_color("#1a2b3c").unwrap(),
+            Color(0x1a, 0x2b, 0x3c, 255)
+        );
+        assert_eq!(
+            parse_color("#1a2b3c80").unwrap(),
+            Color(0x1a, 0x2b, 0x3c, 0x80)
+        );
+    }
+
+    #[test]
+    fn rejects_other_colors() {
+        for color in ["", "fff", "#ff", "#+fffff", "#gggggg", "black", "#1a2b3c4"] {
+            assert!(parse_color(color).is_err(), "{color}");
+        }
+    }
+}
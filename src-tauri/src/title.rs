@@ -0,0 +1,102 @@
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::{main_window, safe_mode, settings};
+
+/// Longest title kept, in characters; window managers truncate well before.
+const MAX_TITLE_CHARS: usize = 200;
+/// Replaced with the app name in titles, e.g. `my-project — {app}`.
+const APP_PLACEHOLDER: &str = "{app}";
+
+/// Drops control characters (newlines included) and surrounding whitespace,
+/// and caps the length.
+fn sanitize_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .chars()
+        .take(MAX_TITLE_CHARS)
+        .collect()
+}
+
+/// Expands `template` into a title; an empty one gives the app name.
+fn render(template: &str, app_name: &str) -> String {
+    let title = sanitize_title(&template.replace(APP_PLACEHOLDER, app_name));
+    if title.is_empty() {
+        app_name.to_string()
+    } else {
+        title
+    }
+}
+
+/// Applies the persisted title template to the main window, if one was saved.
+pub fn restore(app: &AppHandle) {
+    if safe_mode::is_active(app) {
+        return;
+    }
+    let Some(template) = settings::get::<String>(app, settings::WINDOW_TITLE) else {
+        return;
+    };
+    if let Ok(window) = main_window::window(app) {
+        let _ = window.set_title(&render(&template, &app.package_info().name));
+    }
+}
+
+/// Sets the main window title; `{app}` in `title` is replaced with the app
+/// name, and an empty title resets it to just the app name. With `persist`,
+/// the template is restored on the next launch.
+#[tauri::command]
+pub fn set_window_title(
+    app: AppHandle,
+    title: String,
+    persist: Option<bool>,
+) -> Result<String, AppError> {
+    let window = main_window::window(&app)?;
+    let rendered = render(&title, &app.package_info().name);
+    window
+        .set_title(&rendered)
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    if persist.unwrap_or(false) {
+        match sanitize_title(&title) {
+            template if template.is_empty() => settings::clear(&app, settings::WINDOW_TITLE),
+            template => settings::update(&app, settings::WINDOW_TITLE, template),
+        }
+        .map_err(AppError::Store)?;
+    }
+    Ok(rendered)
+}
+
+#[tauri::command]
+pub fn get_window_title(app: AppHandle) -> Result<String, AppError> {
+    main_window::window(&app)?
+        .title()
+        .map_err(|e| AppError::Window(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_control_characters_and_caps_length() {
+        assert_eq!(sanitize_title("  my\nproject\u{7}\t "), "myproject");
+        assert_eq!(
+            sanitize_title(&"é".repeat(MAX_TITLE_CHARS + 10))
+                .chars()
+                .count(),
+            MAX_TITLE_CHARS
+        );
+    }
+
+    #[test]
+    fn renders_templates_with_the_app_name() {
+        assert_eq!(
+            render("my-project — {app}", "bolt.gives"),
+            "my-project — bolt.gives"
+        );
+        assert_eq!(render("", "bolt.gives"), "bolt.gives");
+        assert_eq!(render("\n\t", "bolt.gives"), "bolt.gives");
+    }
+}
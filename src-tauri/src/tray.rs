@@ -0,0 +1,90 @@
+use tauri::menu::{Menu, MenuEvent, MenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::AppHandle;
+
+use crate::main_window::{self, CloseBehavior};
+use crate::{settings, updater};
+
+const TRAY_ID: &str = "main";
+
+/// Creates the tray icon unless it already exists.
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    if app.tray_by_id(TRAY_ID).is_some() {
+        return Ok(());
+    }
+
+    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
+    let check = MenuItem::with_id(
+        app,
+        "check-updates",
+        "Check for Updates",
+        true,
+        None::<&str>,
+    )?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show, &check, &quit])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("bolt.gives")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(on_tray_icon_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "show" => main_window::show(app),
+        "check-updates" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = updater::show_update_dialog(app).await;
+            });
+        }
+        "quit" => main_window::quit(app),
+        _ => {}
+    }
+}
+
+fn on_tray_icon_event(tray: &TrayIcon, event: TrayIconEvent) {
+    if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+    } = event
+    {
+        main_window::toggle(tray.app_handle());
+    }
+}
+
+/// Shows or removes the tray icon to match the close behavior, after the store
+/// was changed wholesale. It stays while the main window is hidden, since it
+/// is the only way back to it.
+pub fn sync(app: &AppHandle) {
+    if main_window::close_behavior(app) == CloseBehavior::MinimizeToTray {
+        if let Err(e) = create(app) {
+            log::warn!("failed to create the tray icon: {e}");
+        }
+    } else if main_window::window(app).is_ok_and(|window| window.is_visible().unwrap_or(false)) {
+        app.remove_tray_by_id(TRAY_ID);
+    }
+}
+
+/// Shows or removes the tray icon. Closing the window hides it to the tray
+/// while this is on, unless another close behavior has been picked since.
+#[tauri::command]
+pub fn set_minimize_to_tray(app: AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, settings::MINIMIZE_TO_TRAY, enabled)?;
+    if enabled {
+        settings::clear(&app, settings::CLOSE_BEHAVIOR)?;
+        create(&app).map_err(|e| e.to_string())
+    } else {
+        app.remove_tray_by_id(TRAY_ID);
+        Ok(())
+    }
+}
@@ -0,0 +1,1323 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use tauri_plugin_http::reqwest::Client;
+use tauri_plugin_updater::{Error as UpdaterError, Update, Updater, UpdaterExt};
+use tokio::sync::watch;
+use tokio::time::Instant;
+use url::Url;
+
+use crate::error::AppError;
+use crate::mock_update::{self, MockUpdate};
+use crate::{
+    disk, http, main_window, notifications, progress, settings, shutdown, version, wake_lock,
+};
+
+/// How often the background task polls the update endpoint unless configured.
+const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 4;
+pub const MAX_CHECK_INTERVAL_HOURS: u64 = 24 * 365;
+
+/// How long "Later" holds off the background prompt, matching the default
+/// check interval it used to wait for.
+const DEFAULT_SNOOZE_HOURS: u64 = 4;
+const MAX_SNOOZE_HOURS: u64 = 24 * 30;
+
+/// Retry delays while offline: doubling from the first, up to the cap (or the
+/// check interval, if that is shorter).
+const OFFLINE_RETRY_INITIAL: Duration = Duration::from_secs(60);
+const OFFLINE_RETRY_MAX: Duration = Duration::from_secs(60 * 60);
+
+/// Tries at downloading an update before giving up, and the delay before the
+/// first retry, doubling after that.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+const DOWNLOAD_RETRY_INITIAL: Duration = Duration::from_secs(2);
+
+/// How long a manifest check is reused unless `update-cache-minutes` says otherwise.
+const DEFAULT_CACHE_MINUTES: u64 = 15;
+
+/// Free space required per byte of the update package: room for the download
+/// plus whatever the installer unpacks.
+const SPACE_FACTOR: u64 = 2;
+
+const DOWNLOAD_PROGRESS_EVENT: &str = "update://download-progress";
+const DOWNLOAD_FINISHED_EVENT: &str = "update://download-finished";
+const ERROR_EVENT: &str = "update://error";
+const READY_TO_RESTART_EVENT: &str = "update://ready-to-restart";
+const INSTALLING_ON_EXIT_EVENT: &str = "update://installing-on-exit";
+const RETRY_EVENT: &str = "update-retry";
+const CHECK_OFFLINE_EVENT: &str = "update-check-offline";
+const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+
+/// Manifest for the beta channel. Stable uses the endpoint from `tauri.conf.json`.
+const BETA_ENDPOINT: &str =
+    "https://github.com/embire2/bolt.gives/releases/download/beta/latest.json";
+
+/// Most custom endpoints accepted, which is plenty for a mirror and fallbacks.
+const MAX_ENDPOINTS: usize = 10;
+
+/// How long [`test_update_connectivity`] waits for each endpoint.
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+/// Feeds interval changes to the running background check; `0` disables it.
+pub struct CheckSchedule(watch::Sender<u64>);
+
+impl CheckSchedule {
+    /// Creates the schedule from the stored interval.
+    pub fn new(app: &AppHandle) -> Self {
+        let hours = settings::get::<u64>(app, settings::UPDATE_CHECK_INTERVAL_HOURS)
+            .unwrap_or(DEFAULT_CHECK_INTERVAL_HOURS);
+        Self(watch::channel(hours).0)
+    }
+
+    /// A receiver to hand to [`check_update`].
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.0.subscribe()
+    }
+}
+
+/// Set while an update is downloading or installing, so only one runs at a time.
+#[derive(Default)]
+pub struct UpdateInProgress(AtomicBool);
+
+impl UpdateInProgress {
+    /// Claims the flag, or returns `None` if another install already holds it.
+    fn begin(&self) -> Option<InstallGuard<'_>> {
+        (!self.0.swap(true, Ordering::SeqCst)).then_some(InstallGuard(&self.0))
+    }
+}
+
+/// Clears [`UpdateInProgress`] when the install finishes or fails.
+struct InstallGuard<'a>(&'a AtomicBool);
+
+impl Drop for InstallGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The result of the latest manifest check, reused by later checks until it
+/// goes stale and by installs so they don't hit the manifest again.
+#[derive(Default)]
+pub struct AvailableUpdate(Mutex<Option<CachedCheck>>);
+
+#[derive(Clone)]
+struct CachedCheck {
+    update: Option<Update>,
+    fetched_at: Instant,
+}
+
+impl AvailableUpdate {
+    fn clear(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
+
+fn cache_ttl(app: &AppHandle) -> Duration {
+    let minutes =
+        settings::get::<u64>(app, settings::UPDATE_CACHE_MINUTES).unwrap_or(DEFAULT_CACHE_MINUTES);
+    Duration::from_secs(minutes.saturating_mul(60))
+}
+
+fn is_fresh(fetched_at: Instant, now: Instant, ttl: Duration) -> bool {
+    now.saturating_duration_since(fetched_at) < ttl
+}
+
+const ALREADY_INSTALLING: &str = "an update is already being installed";
+const UPDATE_CANCELLED: &str = "the update download was cancelled";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub notes: Option<String>,
+    pub pub_date: Option<String>,
+}
+
+impl From<&MockUpdate> for UpdateInfo {
+    fn from(mock: &MockUpdate) -> Self {
+        Self {
+            version: mock.version.clone(),
+            current_version: env!("CARGO_PKG_VERSION").to_string(),
+            notes: mock.notes.clone(),
+            pub_date: None,
+        }
+    }
+}
+
+impl From<&Update> for UpdateInfo {
+    fn from(update: &Update) -> Self {
+        Self {
+            version: update.version.clone(),
+            current_version: update.current_version.clone(),
+            notes: update.body.clone(),
+            pub_date: update.date.map(|date| date.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    UpToDate,
+    UpdateAvailable,
+    Offline,
+    Failed,
+}
+
+/// How reaching one update endpoint went, for [`test_update_connectivity`].
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointReport {
+    /// The manifest URL requested, with the placeholders filled in.
+    pub url: String,
+    /// Whether the server answered at all, whatever the status.
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivityReport {
+    /// Whether requests went through the configured proxy.
+    pub proxied: bool,
+    pub endpoints: Vec<EndpointReport>,
+}
+
+/// Outcome of the most recent update check, kept in the store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastCheck {
+    pub timestamp_ms: u64,
+    pub status: CheckStatus,
+    /// The version found, for [`CheckStatus::UpdateAvailable`].
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OfflinePayload {
+    pub message: String,
+    pub retry_in_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressPayload {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+/// Payload of `update-retry`: the download attempt about to start.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryPayload {
+    pub attempt: u32,
+    pub attempts: u32,
+    pub retry_in_secs: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorPayload {
+    pub reason: &'static str,
+    pub message: String,
+}
+
+/// Machine-readable category for an updater failure.
+fn error_reason(error: &UpdaterError) -> &'static str {
+    match error {
+        UpdaterError::Minisign(_) | UpdaterError::Base64(_) | UpdaterError::SignatureUtf8(_) => {
+            "signature"
+        }
+        UpdaterError::Reqwest(_) | UpdaterError::Network(_) => "network",
+        UpdaterError::ReleaseNotFound
+        | UpdaterError::TargetNotFound(_)
+        | UpdaterError::TargetsNotFound(_)
+        | UpdaterError::Serialization(_) => "manifest",
+        _ => "other",
+    }
+}
+
+/// Whether `error` means the endpoint couldn't be reached at all, as opposed
+/// to it answering with something unusable.
+fn is_offline(error: &UpdaterError) -> bool {
+    match error {
+        UpdaterError::Reqwest(e) => e.is_connect() || e.is_timeout(),
+        _ => false,
+    }
+}
+
+/// Whether a failed download is worth another try: the connection dropped or
+/// the server had a problem. Signature and disk errors would only repeat.
+fn is_transient(error: &UpdaterError) -> bool {
+    match error {
+        UpdaterError::Reqwest(e) => !e.is_builder(),
+        UpdaterError::Network(_) => true,
+        _ => false,
+    }
+}
+
+/// Delay before download attempt `attempt + 1`.
+fn download_retry_delay(attempt: u32) -> Duration {
+    DOWNLOAD_RETRY_INITIAL * 2u32.saturating_pow(attempt.saturating_sub(1))
+}
+
+/// Delay before retrying the `failures`th offline check in a row.
+fn offline_retry_delay(failures: u32, period: Duration) -> Duration {
+    let cap = OFFLINE_RETRY_MAX.min(period);
+    OFFLINE_RETRY_INITIAL
+        .checked_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .map_or(cap, |delay| delay.min(cap))
+}
+
+/// Logs an updater failure and tells the frontend when the signature check failed,
+/// since that otherwise looks like auto-update silently doing nothing.
+fn report_error(app: &AppHandle, error: &UpdaterError) {
+    let reason = error_reason(error);
+    log::error!("updater failed ({reason}): {error}");
+    if reason == "signature" {
+        let _ = app.emit_to(
+            main_window::LABEL,
+            ERROR_EVENT,
+            ErrorPayload {
+                reason,
+                message: error.to_string(),
+            },
+        );
+    }
+}
+
+/// Builds an updater pointed at `endpoint`, or the manifest for the selected
+/// channel, going through the configured proxy if there is one.
+fn updater(app: &AppHandle, endpoint: Option<&Url>) -> Result<Updater, UpdaterError> {
+    let channel = settings::get::<UpdateChannel>(app, settings::UPDATE_CHANNEL).unwrap_or_default();
+    let mut builder = match (endpoint, channel) {
+        (Some(endpoint), _) => app.updater_builder().endpoints(vec![endpoint.clone()])?,
+        (None, UpdateChannel::Stable) => app.updater_builder(),
+        (None, UpdateChannel::Beta) => app
+            .updater_builder()
+            .endpoints(vec![Url::parse(BETA_ENDPOINT)?])?,
+    };
+    if let Some(proxy) = http::proxy(app) {
+        builder = builder.proxy(proxy);
+    }
+    builder.build()
+}
+
+/// Parses and checks endpoint URLs for [`set_update_endpoints`], dropping
+/// duplicates.
+pub fn validate_endpoints(urls: &[String]) -> Result<Vec<Url>, AppError> {
+    if urls.len() > MAX_ENDPOINTS {
+        return Err(AppError::InvalidInput(format!(
+            "at most {MAX_ENDPOINTS} update endpoints can be set"
+        )));
+    }
+    let mut endpoints: Vec<Url> = Vec::new();
+    for url in urls {
+        let endpoint = Url::parse(url.trim()).map_err(|e| {
+            AppError::InvalidInput(format!("invalid update endpoint \"{url}\": {e}"))
+        })?;
+        if endpoint.scheme() != "https" || endpoint.host_str().is_none() {
+            return Err(AppError::InvalidInput(format!(
+                "update endpoint \"{url}\" must be an https:// URL"
+            )));
+        }
+        if !endpoints.contains(&endpoint) {
+            endpoints.push(endpoint);
+        }
+    }
+    Ok(endpoints)
+}
+
+/// `endpoints` with `preferred` (the last one that worked) moved to the front.
+fn ordered_endpoints(mut endpoints: Vec<Url>, preferred: Option<&str>) -> Vec<Url> {
+    if let Some(index) = endpoints
+        .iter()
+        .position(|endpoint| Some(endpoint.as_str()) == preferred)
+    {
+        let endpoint = endpoints.remove(index);
+        endpoints.insert(0, endpoint);
+    }
+    endpoints
+}
+
+/// The custom endpoints to try, in order; empty when the built-in ones apply.
+fn custom_endpoints(app: &AppHandle) -> Vec<Url> {
+    let stored = settings::get::<Vec<String>>(app, settings::UPDATE_ENDPOINTS).unwrap_or_default();
+    let preferred = settings::get::<String>(app, settings::PREFERRED_UPDATE_ENDPOINT);
+    ordered_endpoints(
+        validate_endpoints(&stored).unwrap_or_default(),
+        preferred.as_deref(),
+    )
+}
+
+/// Every endpoint a check may use, in the order it would try them.
+fn configured_endpoints(app: &AppHandle) -> Vec<Url> {
+    let custom = custom_endpoints(app);
+    if !custom.is_empty() {
+        return custom;
+    }
+    let channel = settings::get::<UpdateChannel>(app, settings::UPDATE_CHANNEL).unwrap_or_default();
+    let urls: Vec<String> = match channel {
+        UpdateChannel::Stable => app
+            .config()
+            .plugins
+            .0
+            .get("updater")
+            .and_then(|updater| updater.get("endpoints"))
+            .and_then(|endpoints| serde_json::from_value(endpoints.clone()).ok())
+            .unwrap_or_default(),
+        UpdateChannel::Beta => vec![BETA_ENDPOINT.to_string()],
+    };
+    urls.iter().filter_map(|url| Url::parse(url).ok()).collect()
+}
+
+/// The manifest URL the updater requests for `endpoint`, with `{{target}}`,
+/// `{{arch}}` and `{{current_version}}` filled in the same way.
+fn manifest_url(endpoint: &Url, target: &str, arch: &str, version: &str) -> Option<Url> {
+    let mut url = endpoint.to_string();
+    for (name, value) in [
+        ("target", target),
+        ("arch", arch),
+        ("current_version", version),
+    ] {
+        url = url
+            .replace(&format!("{{{{{name}}}}}"), value)
+            .replace(&format!("%7B%7B{name}%7D%7D"), value);
+    }
+    Url::parse(&url).ok()
+}
+
+/// The OS and architecture names the updater uses in endpoint placeholders.
+fn updater_target() -> (&'static str, &'static str) {
+    let target = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86" => "i686",
+        "arm" => "armv7",
+        arch => arch,
+    };
+    (target, arch)
+}
+
+/// Requests the manifest at `url` and times the answer.
+async fn probe(client: &Client, url: Url) -> EndpointReport {
+    let started = Instant::now();
+    let result = client.get(url.clone()).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(response) => EndpointReport {
+            url: url.to_string(),
+            reachable: true,
+            status: Some(response.status().as_u16()),
+            latency_ms: Some(latency_ms),
+            error: None,
+        },
+        Err(e) => EndpointReport {
+            url: url.to_string(),
+            reachable: false,
+            status: None,
+            latency_ms: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Checks the custom endpoints in order until one answers, remembering it as
+/// preferred for next time, or the built-in ones if none are configured.
+async fn check_endpoints(app: &AppHandle) -> Result<Option<Update>, UpdaterError> {
+    let endpoints = custom_endpoints(app);
+    if endpoints.is_empty() {
+        return updater(app, None)?.check().await;
+    }
+    let mut last_error = None;
+    for endpoint in &endpoints {
+        let result = match updater(app, Some(endpoint)) {
+            Ok(updater) => updater.check().await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(update) => {
+                let _ = settings::set(app, settings::PREFERRED_UPDATE_ENDPOINT, endpoint.as_str());
+                return Ok(update);
+            }
+            Err(e) => {
+                log::warn!("update endpoint {endpoint} failed, trying the next one: {e}");
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or(UpdaterError::EmptyEndpoints))
+}
+
+/// Checks the manifest, caches the result in [`AvailableUpdate`] and records
+/// the outcome as the last check.
+async fn fetch_update(app: &AppHandle) -> Result<Option<Update>, UpdaterError> {
+    let result = check_endpoints(app).await;
+    record_check(app, &result);
+    let update = result?;
+    *app.state::<AvailableUpdate>().0.lock().unwrap() = Some(CachedCheck {
+        update: update.clone(),
+        fetched_at: Instant::now(),
+    });
+    Ok(update)
+}
+
+/// The cached check while it is younger than `update-cache-minutes`, otherwise
+/// (or when `force` is set) a fresh one.
+async fn check_cached(app: &AppHandle, force: bool) -> Result<Option<Update>, UpdaterError> {
+    let cached = app.state::<AvailableUpdate>().0.lock().unwrap().clone();
+    match cached {
+        Some(cached) if !force && is_fresh(cached.fetched_at, Instant::now(), cache_ttl(app)) => {
+            Ok(cached.update)
+        }
+        _ => fetch_update(app).await,
+    }
+}
+
+fn record_check(app: &AppHandle, result: &Result<Option<Update>, UpdaterError>) {
+    let (status, version, error) = match result {
+        Ok(Some(update)) => (
+            CheckStatus::UpdateAvailable,
+            Some(update.version.clone()),
+            None,
+        ),
+        Ok(None) => (CheckStatus::UpToDate, None, None),
+        Err(e) if is_offline(e) => (CheckStatus::Offline, None, Some(e.to_string())),
+        Err(e) => (CheckStatus::Failed, None, Some(e.to_string())),
+    };
+    let check = LastCheck {
+        timestamp_ms: now_ms(),
+        status,
+        version,
+        error,
+    };
+    if let Err(e) = settings::set(app, settings::LAST_UPDATE_CHECK, check) {
+        log::warn!("failed to record the update check: {e}");
+    }
+}
+
+/// When updates were last checked for and what came of it.
+#[tauri::command]
+pub fn get_last_update_check(app: AppHandle) -> Option<LastCheck> {
+    settings::get(&app, settings::LAST_UPDATE_CHECK)
+}
+
+/// The update found by the last check however old it is, so the version
+/// installed is the one the UI showed, or a fresh check if none was found.
+async fn available_update(app: &AppHandle) -> Result<Option<Update>, UpdaterError> {
+    let cached = app.state::<AvailableUpdate>().0.lock().unwrap().clone();
+    match cached.and_then(|cached| cached.update) {
+        Some(update) => Ok(Some(update)),
+        None => fetch_update(app).await,
+    }
+}
+
+/// Checks for an update, reusing a recent check unless `force` is set.
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    force: Option<bool>,
+) -> Result<Option<UpdateInfo>, AppError> {
+    // Debug builds are never signed, so there is nothing to update to.
+    if cfg!(debug_assertions) {
+        return Ok(mock_update::update(&app).map(|mock| UpdateInfo::from(&mock)));
+    }
+    let update = check_cached(&app, force.unwrap_or(false)).await?;
+    Ok(update.as_ref().map(UpdateInfo::from))
+}
+
+#[tauri::command]
+pub async fn show_update_dialog(app: AppHandle) -> Result<(), AppError> {
+    // Debug builds only have the mock updater to offer.
+    if cfg!(debug_assertions) {
+        if let Some(mock) = mock_update::update(&app) {
+            prompt_checked(&app, Offer::Mock(mock));
+        }
+        return Ok(());
+    }
+
+    match check_cached(&app, false).await? {
+        Some(update) => prompt_checked(&app, Offer::Real(Box::new(update))),
+        None => app
+            .dialog()
+            .message("You're running the latest version of bolt.gives.")
+            .title("No updates available")
+            .show(|_| {}),
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, channel: String) -> Result<(), String> {
+    let channel = match channel.as_str() {
+        "stable" => UpdateChannel::Stable,
+        "beta" => UpdateChannel::Beta,
+        _ => return Err(format!("unknown update channel \"{channel}\"")),
+    };
+    settings::update(&app, settings::UPDATE_CHANNEL, channel)?;
+    // The cached check came from the other channel's manifest.
+    app.state::<AvailableUpdate>().clear();
+    Ok(())
+}
+
+/// The custom update endpoints; empty when the built-in ones are used.
+#[tauri::command]
+pub fn get_update_endpoints(app: AppHandle) -> Vec<String> {
+    settings::get(&app, settings::UPDATE_ENDPOINTS).unwrap_or_default()
+}
+
+/// Requests the manifest of every update endpoint in turn to tell network
+/// problems from server ones. Only the small manifest is fetched, never a package.
+#[tauri::command]
+pub async fn test_update_connectivity(app: AppHandle) -> Result<ConnectivityReport, AppError> {
+    let client = http::client(&app, CONNECTIVITY_TIMEOUT).map_err(AppError::Io)?;
+    let (target, arch) = updater_target();
+    let version = app.package_info().version.to_string();
+    let mut endpoints = Vec::new();
+    for endpoint in configured_endpoints(&app) {
+        let report = match manifest_url(&endpoint, target, arch, &version) {
+            Some(url) => probe(&client, url).await,
+            None => EndpointReport {
+                url: endpoint.to_string(),
+                reachable: false,
+                status: None,
+                latency_ms: None,
+                error: Some("invalid URL after filling in the placeholders".to_string()),
+            },
+        };
+        endpoints.push(report);
+    }
+    Ok(ConnectivityReport {
+        proxied: http::proxy(&app).is_some(),
+        endpoints,
+    })
+}
+
+/// Replaces the built-in update endpoints with `urls`, tried in order until
+/// one answers; an empty list goes back to the built-in ones.
+#[tauri::command]
+pub fn set_update_endpoints(app: AppHandle, urls: Vec<String>) -> Result<(), AppError> {
+    let endpoints = validate_endpoints(&urls)?;
+    if endpoints.is_empty() {
+        settings::clear(&app, settings::UPDATE_ENDPOINTS)
+    } else {
+        let urls: Vec<&str> = endpoints.iter().map(Url::as_str).collect();
+        settings::update(&app, settings::UPDATE_ENDPOINTS, urls)
+    }
+    .map_err(AppError::Store)?;
+    settings::remove(&app, settings::PREFERRED_UPDATE_ENDPOINT).map_err(AppError::Store)?;
+    app.state::<AvailableUpdate>().clear();
+    Ok(())
+}
+
+/// Installs the available update without any native dialog, for custom update UIs.
+/// Reuses the update found by the last check, so the version installed is the
+/// one the UI showed. Progress is reported through the `update://download-*`
+/// events.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), AppError> {
+    let mock = mock_update::update(&app);
+    if cfg!(debug_assertions) && mock.is_none() {
+        return Err(AppError::Updater(
+            "updates are disabled in debug builds; set mock-update to try the flow".to_string(),
+        ));
+    }
+    let in_progress = app.state::<UpdateInProgress>();
+    let Some(_installing) = in_progress.begin() else {
+        return Err(AppError::Updater(ALREADY_INSTALLING.to_string()));
+    };
+    if let Some(mock) = mock {
+        install_mock(&app, &mock).await?;
+        let _ = app.emit_to(main_window::LABEL, READY_TO_RESTART_EVENT, &mock.version);
+        return Ok(());
+    }
+
+    let update = available_update(&app)
+        .await?
+        .ok_or_else(|| AppError::Updater("no update is available".to_string()))?;
+    install(&app, &update).await?;
+    let _ = app.emit_to(main_window::LABEL, READY_TO_RESTART_EVENT, &update.version);
+    Ok(())
+}
+
+/// Installs `version` when the app next quits instead of right away.
+#[tauri::command]
+pub fn defer_update(app: AppHandle, version: String) -> Result<(), AppError> {
+    let version = version.trim();
+    if version.is_empty() {
+        return Err(AppError::InvalidInput(
+            "a version to defer is required".to_string(),
+        ));
+    }
+    settings::set(&app, settings::PENDING_UPDATE_VERSION, version).map_err(AppError::Store)
+}
+
+/// Forgets a deferred update. Called on launch, since by then it has either
+/// been installed or the user quit without it.
+pub fn clear_deferred(app: &AppHandle) {
+    let _ = settings::remove(app, settings::PENDING_UPDATE_VERSION);
+}
+
+/// Whether the update now `available` can stand in for the `deferred` one:
+/// the same version or a newer one, which the user would be offered next.
+fn supersedes(available: &str, deferred: &str) -> bool {
+    version::compare(available, deferred).is_ok_and(|order| order.is_ge())
+}
+
+/// Starts installing a deferred update in place of exiting. If a newer
+/// version came out since it was deferred, that one is installed instead.
+///
+/// Returns `true` if it did, in which case the caller must hold the exit; the
+/// app exits with `code` once the install is done, whether or not it worked.
+pub fn install_on_exit(app: &AppHandle, code: i32) -> bool {
+    if cfg!(debug_assertions) {
+        return false;
+    }
+    let Some(version) = settings::get::<String>(app, settings::PENDING_UPDATE_VERSION) else {
+        return false;
+    };
+    // Cleared up front so the exit after the install goes straight through.
+    clear_deferred(app);
+    if app.state::<UpdateInProgress>().0.load(Ordering::SeqCst) {
+        return false;
+    }
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        log::info!("installing deferred update {version} before exiting");
+        let _ = handle.emit_to(main_window::LABEL, INSTALLING_ON_EXIT_EVENT, &version);
+        let in_progress = handle.state::<UpdateInProgress>();
+        if let Some(_installing) = in_progress.begin() {
+            match available_update(&handle).await {
+                Ok(Some(update)) if supersedes(&update.version, &version) => {
+                    if update.version != version {
+                        log::info!(
+                            "deferred update {version} was replaced by {}, installing that instead",
+                            update.version
+                        );
+                    }
+                    let _ = install(&handle, &update).await;
+                }
+                Ok(_) => log::warn!("deferred update {version} is no longer available"),
+                Err(e) => report_error(&handle, &e),
+            }
+        }
+        let _ = settings::flush(&handle);
+        handle.exit(code);
+    });
+    true
+}
+
+/// The update found by the last check, unless the user skipped it or it is
+/// already being installed. Never hits the network.
+pub fn pending_update(app: &AppHandle) -> Option<Update> {
+    let cached = app.state::<AvailableUpdate>().0.lock().unwrap().clone();
+    cached
+        .and_then(|cached| cached.update)
+        .filter(|update| !is_dismissed(app, &update.version))
+}
+
+/// Installs `update` and restarts into it, for installs nobody is watching.
+pub async fn install_and_restart(app: &AppHandle, update: &Update) -> Result<(), AppError> {
+    let in_progress = app.state::<UpdateInProgress>();
+    let Some(_installing) = in_progress.begin() else {
+        return Err(AppError::Updater(ALREADY_INSTALLING.to_string()));
+    };
+    install(app, update).await?;
+    main_window::restart(app)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Whether a snooze of `snoozed` until `until_ms` still covers `version`. It
+/// ends at `until_ms`, or as soon as something newer than `snoozed` is out.
+fn is_snoozed(until_ms: u64, snoozed: &str, now_ms: u64, version: &str) -> bool {
+    now_ms < until_ms
+        && version::compare(version, snoozed)
+            .map(|order| order.is_le())
+            .unwrap_or(false)
+}
+
+/// Whether the background check and idle installs should leave `update`
+/// alone. A snooze that has run out or been overtaken by a newer version is
+/// cleared.
+pub fn snoozed(app: &AppHandle, update: &Update) -> bool {
+    let Some(until_ms) = settings::get::<u64>(app, settings::UPDATE_SNOOZE_UNTIL) else {
+        return false;
+    };
+    let version =
+        settings::get::<String>(app, settings::UPDATE_SNOOZED_VERSION).unwrap_or_default();
+    if is_snoozed(until_ms, &version, now_ms(), &update.version) {
+        return true;
+    }
+    let _ = settings::remove(app, settings::UPDATE_SNOOZE_UNTIL);
+    let _ = settings::remove(app, settings::UPDATE_SNOOZED_VERSION);
+    false
+}
+
+fn snooze(app: &AppHandle, version: &str, hours: u64) -> Result<(), AppError> {
+    let until_ms = now_ms().saturating_add(hours * 60 * 60 * 1000);
+    settings::set(app, settings::UPDATE_SNOOZED_VERSION, version).map_err(AppError::Store)?;
+    settings::set(app, settings::UPDATE_SNOOZE_UNTIL, until_ms).map_err(AppError::Store)?;
+    log::info!("update {version} snoozed for {hours}h");
+    Ok(())
+}
+
+/// Holds off the background update prompt for `hours`, or until a newer
+/// version than the one found comes out.
+#[tauri::command]
+pub fn snooze_update(app: AppHandle, hours: u64) -> Result<(), AppError> {
+    if hours == 0 || hours > MAX_SNOOZE_HOURS {
+        return Err(AppError::InvalidInput(format!(
+            "updates can be snoozed for 1 to {MAX_SNOOZE_HOURS} hours"
+        )));
+    }
+    let version = pending_update(&app)
+        .map(|update| update.version)
+        .or_else(|| mock_update::update(&app).map(|mock| mock.version))
+        .ok_or_else(|| AppError::InvalidInput("there is no update to snooze".to_string()))?;
+    snooze(&app, &version, hours)
+}
+
+/// Whether an update is currently downloading or installing.
+#[tauri::command]
+pub fn is_update_in_progress(in_progress: State<'_, UpdateInProgress>) -> bool {
+    in_progress.0.load(Ordering::SeqCst)
+}
+
+/// Sets the background check interval in hours; `0` turns automatic checks off.
+#[tauri::command]
+pub fn set_update_check_interval(app: AppHandle, hours: u64) -> Result<(), String> {
+    if hours > MAX_CHECK_INTERVAL_HOURS {
+        return Err(format!(
+            "update check interval must be at most {MAX_CHECK_INTERVAL_HOURS} hours"
+        ));
+    }
+    settings::update(&app, settings::UPDATE_CHECK_INTERVAL_HOURS, hours)?;
+    app.state::<CheckSchedule>().0.send_replace(hours);
+    Ok(())
+}
+
+/// Polls for updates in the background and prompts whenever one is found.
+///
+/// The loop restarts whenever the interval in `hours` changes, and ends on
+/// shutdown. While the endpoint is unreachable it retries sooner, backing off
+/// from [`OFFLINE_RETRY_INITIAL`], and tells the frontend with
+/// `update-check-offline`.
+pub async fn check_update(app: AppHandle, mut hours: watch::Receiver<u64>) {
+    let mut stop = shutdown::subscribe(&app);
+    let mut first_run = true;
+    loop {
+        let current = (*hours.borrow_and_update()).min(MAX_CHECK_INTERVAL_HOURS);
+        if current == 0 {
+            tokio::select! {
+                changed = hours.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                _ = shutdown::requested(&mut stop) => return,
+            }
+            continue;
+        }
+
+        // Check right away on launch, but not every time the interval is changed.
+        let period = Duration::from_secs(current * 60 * 60);
+        let mut next = if first_run {
+            Instant::now()
+        } else {
+            Instant::now() + period
+        };
+        first_run = false;
+        let mut failures = 0;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(next) => {
+                    next = Instant::now() + period;
+                    match check_cached(&app, false).await {
+                        Ok(Some(update)) => offer_update(&app, update),
+                        Ok(None) => {}
+                        Err(e) if is_offline(&e) => {
+                            failures += 1;
+                            let retry_in = offline_retry_delay(failures, period);
+                            next = Instant::now() + retry_in;
+                            log::info!("offline, checking for updates again in {}s: {e}", retry_in.as_secs());
+                            let _ = app.emit_to(
+                                main_window::LABEL,
+                                CHECK_OFFLINE_EVENT,
+                                OfflinePayload {
+                                    message: e.to_string(),
+                                    retry_in_secs: retry_in.as_secs(),
+                                },
+                            );
+                            continue;
+                        }
+                        Err(e) => report_error(&app, &e),
+                    }
+                    failures = 0;
+                },
+                changed = hours.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    break;
+                }
+                _ = shutdown::requested(&mut stop) => return,
+            }
+        }
+    }
+}
+
+/// Fails early with a readable error when the temp volume can't fit `update`.
+///
+/// The manifest doesn't carry a size, so it is taken from a `HEAD` request for
+/// the package; the check is skipped when the server doesn't report one.
+async fn ensure_free_space(app: &AppHandle, update: &Update) -> Result<(), AppError> {
+    let Some(size) = http::content_length(app, &update.download_url).await else {
+        return Ok(());
+    };
+    let dir = std::env::temp_dir();
+    let available = disk::free_space(&dir)?;
+    let required = size.saturating_mul(SPACE_FACTOR);
+    log::info!(
+        "update {} needs {required} bytes free, {available} available in {}",
+        update.version,
+        dir.display()
+    );
+    if available < required {
+        return Err(AppError::InsufficientSpace(format!(
+            "Not enough disk space to install bolt.gives {}: {} needed, {} available.",
+            update.version,
+            disk::format_size(required),
+            disk::format_size(available)
+        )));
+    }
+    Ok(())
+}
+
+/// Shows download progress on the taskbar and tells the main window.
+fn report_progress(app: &AppHandle, downloaded: u64, total: Option<u64>) {
+    let percent = total
+        .filter(|total| *total > 0)
+        .map(|total| (downloaded as f64 / total as f64 * 100.0).min(100.0));
+    progress::report(app, percent);
+    let _ = app.emit_to(
+        main_window::LABEL,
+        DOWNLOAD_PROGRESS_EVENT,
+        ProgressPayload {
+            downloaded,
+            total,
+            percent,
+        },
+    );
+}
+
+/// Downloads `update`, reporting progress to the main window. A download that
+/// fails on the way is retried with backoff, starting over with a fresh
+/// buffer; a bad signature is not.
+async fn download(app: &AppHandle, update: &Update) -> Result<Vec<u8>, UpdaterError> {
+    let mut attempt = 1;
+    loop {
+        let mut downloaded = 0u64;
+        let result = update
+            .download(
+                |chunk_length, content_length| {
+                    downloaded += chunk_length as u64;
+                    report_progress(app, downloaded, content_length);
+                },
+                || {
+                    let _ = app.emit_to(main_window::LABEL, DOWNLOAD_FINISHED_EVENT, ());
+                },
+            )
+            .await;
+        match result {
+            Err(e) if attempt < DOWNLOAD_ATTEMPTS && is_transient(&e) => {
+                let retry_in = download_retry_delay(attempt);
+                attempt += 1;
+                log::warn!(
+                    "update download failed, trying again in {}s (attempt {attempt} of {DOWNLOAD_ATTEMPTS}): {e}",
+                    retry_in.as_secs()
+                );
+                let _ = app.emit_to(
+                    main_window::LABEL,
+                    RETRY_EVENT,
+                    RetryPayload {
+                        attempt,
+                        attempts: DOWNLOAD_ATTEMPTS,
+                        retry_in_secs: retry_in.as_secs(),
+                        message: e.to_string(),
+                    },
+                );
+                tokio::time::sleep(retry_in).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Goes through the download events of an install without installing
+/// anything, for the mock updater.
+async fn install_mock(app: &AppHandle, mock: &MockUpdate) -> Result<(), AppError> {
+    log::info!("pretending to install mock update {}", mock.version);
+    let active = crate::download::begin(app);
+    let mut downloaded = 0u64;
+    let finished = tokio::select! {
+        () = mock_update::download(|chunk_length, content_length| {
+            downloaded += chunk_length as u64;
+            report_progress(app, downloaded, content_length);
+        }) => true,
+        () = active.cancelled() => false,
+    };
+    drop(active);
+    progress::clear(app);
+    if !finished {
+        crate::download::report_cancelled(app, None);
+        return Err(AppError::Cancelled(UPDATE_CANCELLED.to_string()));
+    }
+    let _ = app.emit_to(main_window::LABEL, DOWNLOAD_FINISHED_EVENT, ());
+    Ok(())
+}
+
+/// Downloads and installs `update`, reporting progress to the main window.
+/// `cancel_downloads` stops the download, but not an install under way.
+///
+/// Callers must hold an [`InstallGuard`] for the duration.
+async fn install(app: &AppHandle, update: &Update) -> Result<(), AppError> {
+    ensure_free_space(app, update).await?;
+    let _awake = wake_lock::acquire(app, "Installing an update");
+    // Installers may close the window to replace the binary; let them.
+    main_window::set_exiting(app, true);
+    let active = crate::download::begin(app);
+    let result = tokio::select! {
+        result = download(app, update) => Some(result.and_then(|bytes| update.install(bytes))),
+        () = active.cancelled() => None,
+    };
+    drop(active);
+    progress::clear(app);
+    main_window::set_exiting(app, false);
+    let Some(result) = result else {
+        crate::download::report_cancelled(app, None);
+        return Err(AppError::Cancelled(UPDATE_CANCELLED.to_string()));
+    };
+    match &result {
+        Ok(()) => app.state::<AvailableUpdate>().clear(),
+        Err(e) => report_error(app, e),
+    }
+    Ok(result?)
+}
+
+const UPDATE_BUTTON: &str = "Update";
+const SKIP_BUTTON: &str = "Skip This Version";
+const LATER_BUTTON: &str = "Later";
+
+/// Whether `version` shouldn't be offered: the user skipped it or an install
+/// is already running.
+fn is_dismissed(app: &AppHandle, version: &str) -> bool {
+    let skipped = settings::get::<String>(app, settings::SKIPPED_VERSION);
+    app.state::<UpdateInProgress>().0.load(Ordering::SeqCst) || skipped.as_deref() == Some(version)
+}
+
+/// An update the native prompt can offer: a real one, or the mock updater's
+/// in a debug build.
+#[derive(Clone)]
+enum Offer {
+    Real(Box<Update>),
+    Mock(MockUpdate),
+}
+
+impl Offer {
+    fn version(&self) -> &str {
+        match self {
+            Offer::Real(update) => &update.version,
+            Offer::Mock(mock) => &mock.version,
+        }
+    }
+
+    fn current_version(&self) -> &str {
+        match self {
+            Offer::Real(update) => &update.current_version,
+            Offer::Mock(_) => env!("CARGO_PKG_VERSION"),
+        }
+    }
+
+    async fn install(&self, app: &AppHandle) -> Result<(), AppError> {
+        match self {
+            Offer::Real(update) => install(app, update).await,
+            Offer::Mock(mock) => install_mock(app, mock).await,
+        }
+    }
+}
+
+/// Offers an update found by the background check. With
+/// `quiet-update-notifications` that is a notification and an
+/// `update-available` event for an in-app badge instead of a modal dialog, so
+/// nothing steals focus.
+fn offer_update(app: &AppHandle, update: Update) {
+    if snoozed(app, &update) {
+        log::debug!("update {} is snoozed", update.version);
+        return;
+    }
+    if is_dismissed(app, &update.version) {
+        return;
+    }
+    if !settings::get::<bool>(app, settings::QUIET_UPDATE_NOTIFICATIONS).unwrap_or(false) {
+        prompt_install(app, Offer::Real(Box::new(update)));
+        return;
+    }
+    let _ = app.emit_to(
+        main_window::LABEL,
+        UPDATE_AVAILABLE_EVENT,
+        UpdateInfo::from(&update),
+    );
+    if let Err(e) = notifications::notify(
+        app.clone(),
+        "Update available".to_string(),
+        format!("bolt.gives {} is ready to install.", update.version),
+        Some(true),
+    ) {
+        log::debug!("couldn't show the update notification: {e}");
+    }
+}
+
+/// Answers "Check for Updates…". Unlike the background check, it offers a
+/// version the user skipped, and says so when an install is already running.
+fn prompt_checked(app: &AppHandle, update: Offer) {
+    if app.state::<UpdateInProgress>().0.load(Ordering::SeqCst) {
+        app.dialog()
+            .message("An update is already being installed.")
+            .title("Update in progress")
+            .show(|_| {});
+        return;
+    }
+    prompt_install(app, update);
+}
+
+/// Asks the user what to do about `update`.
+fn prompt_install(app: &AppHandle, update: Offer) {
+    let mut message = format!(
+        "bolt.gives {} is available (you have {}).",
+        update.version(),
+        update.current_version()
+    );
+    if version::is_major(update.current_version(), update.version()).unwrap_or(false) {
+        message.push_str("\n\nThis is a major update and may change how things work.");
+    }
+    let handle = app.clone();
+    app.dialog()
+        .message(message)
+        .title("Update available")
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            UPDATE_BUTTON.to_string(),
+            SKIP_BUTTON.to_string(),
+            LATER_BUTTON.to_string(),
+        ))
+        .show_with_result(move |result| match result {
+            MessageDialogResult::Yes => install_in_background(handle, update),
+            MessageDialogResult::Custom(label) if label == UPDATE_BUTTON => {
+                install_in_background(handle, update)
+            }
+            MessageDialogResult::No => skip_version(&handle, &update),
+            MessageDialogResult::Custom(label) if label == SKIP_BUTTON => {
+                skip_version(&handle, &update)
+            }
+            _ => {
+                if let Err(e) = snooze(&handle, update.version(), DEFAULT_SNOOZE_HOURS) {
+                    log::warn!("failed to snooze the update: {e}");
+                }
+            }
+        });
+}
+
+fn skip_version(app: &AppHandle, update: &Offer) {
+    let _ = settings::set(app, settings::SKIPPED_VERSION, update.version());
+}
+
+fn install_in_background(handle: AppHandle, update: Offer) {
+    tauri::async_runtime::spawn(async move {
+        let in_progress = handle.state::<UpdateInProgress>();
+        let Some(installing) = in_progress.begin() else {
+            log::info!("{ALREADY_INSTALLING}, ignoring another request");
+            return;
+        };
+        let result = update.install(&handle).await;
+        drop(installing);
+        if let Err(AppError::Cancelled(_)) = result {
+            return;
+        }
+        if let Err(e) = result {
+            handle
+                .dialog()
+                .message(format!("The update could not be installed: {e}"))
+                .title("Update failed")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+            return;
+        }
+
+        let app = handle.clone();
+        handle
+            .dialog()
+            .message("Update installed. Restart now?")
+            .title("Update ready")
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Restart".to_string(),
+                "Later".to_string(),
+            ))
+            .show(move |confirmed| {
+                // Otherwise the update applies on the next launch.
+                if confirmed {
+                    main_window::restart(&app);
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn offline_retries_back_off_up_to_the_cap() {
+        assert_eq!(offline_retry_delay(1, DAY), OFFLINE_RETRY_INITIAL);
+        assert_eq!(offline_retry_delay(2, DAY), OFFLINE_RETRY_INITIAL * 2);
+        assert_eq!(offline_retry_delay(3, DAY), OFFLINE_RETRY_INITIAL * 4);
+        assert_eq!(offline_retry_delay(10, DAY), OFFLINE_RETRY_MAX);
+        assert_eq!(offline_retry_delay(u32::MAX, DAY), OFFLINE_RETRY_MAX);
+    }
+
+    #[test]
+    fn snoozes_until_the_deadline_or_a_newer_version() {
+        assert!(is_snoozed(1_000, "3.1.0", 500, "3.1.0"));
+        assert!(!is_snoozed(1_000, "3.1.0", 1_000, "3.1.0"));
+        assert!(!is_snoozed(1_000, "3.1.0", 500, "3.1.1"));
+        assert!(!is_snoozed(1_000, "", 500, "3.1.0"));
+    }
+
+    #[test]
+    fn deferred_updates_give_way_to_newer_ones() {
+        assert!(supersedes("3.1.0", "3.1.0"));
+        assert!(supersedes("3.2.0", "3.1.0"));
+        assert!(!supersedes("3.0.9", "3.1.0"));
+        assert!(!supersedes("3.2.0", "not a version"));
+    }
+
+    #[test]
+    fn retries_only_transient_download_failures() {
+        assert!(is_transient(&UpdaterError::Network(
+            "Download request failed with status: 503".to_string()
+        )));
+        assert!(!is_transient(&UpdaterError::SignatureUtf8(
+            "bad signature".to_string()
+        )));
+        assert!(!is_transient(&UpdaterError::Io(std::io::Error::other(
+            "disk full"
+        ))));
+        assert_eq!(download_retry_delay(1), DOWNLOAD_RETRY_INITIAL);
+        assert_eq!(download_retry_delay(2), DOWNLOAD_RETRY_INITIAL * 2);
+    }
+
+    #[test]
+    fn validates_update_endpoints() {
+        let endpoints = validate_endpoints(&[
+            "https://mirror.corp/{{target}}/{{arch}}/latest.json".to_string(),
+            " https://mirror.corp/{{target}}/{{arch}}/latest.json ".to_string(),
+            "https://backup.corp/latest.json".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(endpoints.len(), 2);
+        assert!(validate_endpoints(&["http://mirror.corp/latest.json".to_string()]).is_err());
+        assert!(validate_endpoints(&["mirror.corp/latest.json".to_string()]).is_err());
+        assert!(
+            validate_endpoints(&vec!["https://a.corp/".to_string(); MAX_ENDPOINTS + 1]).is_err()
+        );
+    }
+
+    #[test]
+    fn fills_in_manifest_placeholders() {
+        let endpoint = validate_endpoints(&[
+            "https://mirror.corp/{{target}}/{{arch}}/{{current_version}}/latest.json".to_string(),
+        ])
+        .unwrap()
+        .remove(0);
+        assert_eq!(
+            manifest_url(&endpoint, "darwin", "aarch64", "3.0.8")
+                .unwrap()
+                .as_str(),
+            "https://mirror.corp/darwin/aarch64/3.0.8/latest.json"
+        );
+        let plain = Url::parse(BETA_ENDPOINT).unwrap();
+        assert_eq!(
+            manifest_url(&plain, "linux", "x86_64", "3.0.8").unwrap(),
+            plain
+        );
+    }
+
+    #[test]
+    fn tries_the_preferred_endpoint_first() {
+        let endpoints = validate_endpoints(&[
+            "https://a.corp/latest.json".to_string(),
+            "https://b.corp/latest.json".to_string(),
+            "https://c.corp/latest.json".to_string(),
+        ])
+        .unwrap();
+        let hosts = |endpoints: Vec<Url>| -> Vec<String> {
+            endpoints
+                .iter()
+                .map(|endpoint| endpoint.host_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            hosts(ordered_endpoints(
+                endpoints.clone(),
+                Some("https://c.corp/latest.json")
+            )),
+            ["c.corp", "a.corp", "b.corp"]
+        );
+        assert_eq!(
+            hosts(ordered_endpoints(endpoints, Some("https://gone.corp/"))),
+            ["a.corp", "b.corp", "c.corp"]
+        );
+    }
+
+    #[test]
+    fn reuses_checks_within_the_ttl() {
+        let fetched_at = Instant::now();
+        let ttl = Duration::from_secs(15 * 60);
+        assert!(is_fresh(fetched_at, fetched_at, ttl));
+        assert!(is_fresh(fetched_at, fetched_at + ttl / 2, ttl));
+        assert!(!is_fresh(fetched_at, fetched_at + ttl, ttl));
+        // A TTL of zero turns the cache off.
+        assert!(!is_fresh(fetched_at, fetched_at, Duration::ZERO));
+    }
+
+    #[test]
+    fn offline_retries_never_exceed_the_interval() {
+        let period = Duration::from_secs(90);
+        assert_eq!(offline_retry_delay(1, period), OFFLINE_RETRY_INITIAL);
+        assert_eq!(offline_retry_delay(2, period), period);
+    }
+}
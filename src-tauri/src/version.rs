@@ -0,0 +1,75 @@
+use std::cmp::Ordering;
+
+use semver::Version;
+
+use crate::error::AppError;
+
+fn parse(version: &str) -> Result<Version, AppError> {
+    Version::parse(version.trim())
+        .map_err(|e| AppError::InvalidInput(format!("invalid version \"{version}\": {e}")))
+}
+
+/// Orders two versions by semver precedence: pre-releases sort before their
+/// release and build metadata is ignored.
+pub fn compare(a: &str, b: &str) -> Result<Ordering, AppError> {
+    Ok(parse(a)?.cmp_precedence(&parse(b)?))
+}
+
+/// Whether going from `current` to `next` crosses a major version. Below 1.0
+/// the minor version is the breaking one, as semver has it.
+pub fn is_major(current: &str, next: &str) -> Result<bool, AppError> {
+    let (current, next) = (parse(current)?, parse(next)?);
+    Ok(if current.major == 0 && next.major == 0 {
+        next.minor > current.minor
+    } else {
+        next.major > current.major
+    })
+}
+
+/// Compares two semver versions: -1 if `a` is older, 0 if they're the same
+/// release, 1 if `a` is newer.
+#[tauri::command]
+pub fn compare_versions(a: String, b: String) -> Result<i32, AppError> {
+    Ok(compare(&a, &b)? as i32)
+}
+
+#[tauri::command]
+pub fn is_major_upgrade(current: String, next: String) -> Result<bool, AppError> {
+    is_major(&current, &next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_by_precedence() {
+        assert_eq!(compare("3.0.8", "3.1.0").unwrap(), Ordering::Less);
+        assert_eq!(
+            compare("3.1.0-beta.2", "3.1.0-beta.10").unwrap(),
+            Ordering::Less
+        );
+        assert_eq!(compare("3.1.0-rc.1", "3.1.0").unwrap(), Ordering::Less);
+        assert_eq!(
+            compare("3.1.0+build.5", "3.1.0+build.7").unwrap(),
+            Ordering::Equal
+        );
+        assert_eq!(compare("10.0.0", "9.9.9").unwrap(), Ordering::Greater);
+    }
+
+    #[test]
+    fn rejects_unparseable_versions() {
+        assert!(compare("3.1", "3.1.0").is_err());
+        assert!(compare("v3.1.0", "3.1.0").is_err());
+        assert!(is_major("3.0.0", "latest").is_err());
+    }
+
+    #[test]
+    fn detects_major_upgrades() {
+        assert!(is_major("3.0.8", "4.0.0-beta.1").unwrap());
+        assert!(!is_major("3.0.8", "3.9.0").unwrap());
+        assert!(!is_major("4.0.0", "3.0.0").unwrap());
+        assert!(is_major("0.4.2", "0.5.0").unwrap());
+        assert!(!is_major("0.4.2", "0.4.3+build.1").unwrap());
+    }
+}
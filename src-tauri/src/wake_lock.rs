@@ -0,0 +1,200 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::AppError;
+
+/// Keeps the system and display awake while anything holds it. Holders are
+/// counted so an update install and the frontend's own request share one OS
+/// inhibitor, which is released when the last of them lets go.
+#[derive(Default)]
+pub struct WakeLock(Mutex<WakeState>);
+
+#[derive(Default)]
+struct WakeState {
+    holders: usize,
+    /// Whether the frontend holds the lock through [`set_wake_lock`].
+    requested: bool,
+    inhibitor: Option<platform::Inhibitor>,
+}
+
+/// Releases its hold on the [`WakeLock`] when dropped, so the lock goes away
+/// however the work it covers ends.
+pub struct WakeGuard {
+    app: AppHandle,
+}
+
+impl Drop for WakeGuard {
+    fn drop(&mut self) {
+        release(&self.app);
+    }
+}
+
+fn hold(app: &AppHandle, reason: &str) {
+    let state = app.state::<WakeLock>();
+    let mut state = state.0.lock().unwrap();
+    state.holders += 1;
+    if state.holders > 1 {
+        return;
+    }
+    match platform::inhibit(&app.package_info().name, reason) {
+        Ok(inhibitor) => {
+            log::info!("acquired the wake lock: {reason}");
+            state.inhibitor = Some(inhibitor);
+        }
+        Err(e) => log::warn!("failed to acquire the wake lock: {e}"),
+    }
+}
+
+fn release(app: &AppHandle) {
+    let state = app.state::<WakeLock>();
+    let mut state = state.0.lock().unwrap();
+    state.holders = state.holders.saturating_sub(1);
+    if state.holders > 0 {
+        return;
+    }
+    if state.inhibitor.take().is_some() {
+        log::info!("released the wake lock");
+    }
+}
+
+/// Keeps the machine awake until the returned guard is dropped.
+pub fn acquire(app: &AppHandle, reason: &str) -> WakeGuard {
+    hold(app, reason);
+    WakeGuard { app: app.clone() }
+}
+
+/// Keeps the system and display from sleeping while `enabled`, on top of the
+/// lock taken automatically during update downloads.
+#[tauri::command]
+pub fn set_wake_lock(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let requested = {
+        let state = app.state::<WakeLock>();
+        let mut state = state.0.lock().unwrap();
+        std::mem::replace(&mut state.requested, enabled)
+    };
+    match (requested, enabled) {
+        (false, true) => hold(&app, "requested by the app"),
+        (true, false) => release(&app),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The execution state belongs to the thread that set it, so a dedicated
+/// thread holds it until the inhibitor is dropped.
+#[cfg(windows)]
+mod platform {
+    use std::sync::mpsc::{self, Sender};
+    use std::thread::{self, JoinHandle};
+
+    use ::windows::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+    };
+
+    pub struct Inhibitor {
+        release: Option<Sender<()>>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    pub fn inhibit(_app_name: &str, _reason: &str) -> Result<Inhibitor, String> {
+        let (release, released) = mpsc::channel::<()>();
+        let (ready, acquired) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("wake-lock".to_string())
+            .spawn(move || {
+                // SAFETY: plain Win32 call with valid flags; it only affects this thread.
+                let previous = unsafe {
+                    SetThreadExecutionState(
+                        ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED,
+                    )
+                };
+                let _ = ready.send(previous.0 != 0);
+                // Returns once the inhibitor drops its sender.
+                let _ = released.recv();
+                // SAFETY: as above.
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+            })
+            .map_err(|e| e.to_string())?;
+        let inhibitor = Inhibitor {
+            release: Some(release),
+            thread: Some(thread),
+        };
+        if acquired.recv().unwrap_or(false) {
+            Ok(inhibitor)
+        } else {
+            Err("SetThreadExecutionState failed".to_string())
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            drop(self.release.take());
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+/// `caffeinate` holds the power assertions and, watching our PID, exits on
+/// its own if the app dies without releasing it.
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::{Child, Command};
+
+    pub struct Inhibitor(Child);
+
+    pub fn inhibit(_app_name: &str, _reason: &str) -> Result<Inhibitor, String> {
+        Command::new("caffeinate")
+            .args(["-d", "-i", "-w", &std::process::id().to_string()])
+            .spawn()
+            .map(Inhibitor)
+            .map_err(|e| format!("failed to run caffeinate: {e}"))
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+}
+
+/// `systemd-inhibit` holds the lock for as long as the command it runs. That is
+/// `cat` reading a pipe from us, so it ends when we close the pipe or die.
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::process::{Child, Command, Stdio};
+
+    pub struct Inhibitor(Child);
+
+    pub fn inhibit(app_name: &str, reason: &str) -> Result<Inhibitor, String> {
+        Command::new("systemd-inhibit")
+            .arg("--what=idle:sleep")
+            .arg(format!("--who={app_name}"))
+            .arg(format!("--why={reason}"))
+            .args(["--mode=block", "cat"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map(Inhibitor)
+            .map_err(|e| format!("failed to run systemd-inhibit: {e}"))
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            drop(self.0.stdin.take());
+            let _ = self.0.wait();
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+mod platform {
+    pub struct Inhibitor;
+
+    pub fn inhibit(_app_name: &str, _reason: &str) -> Result<Inhibitor, String> {
+        Err("preventing sleep isn't supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,206 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+use crate::error::AppError;
+use crate::{main_window, settings};
+
+const WINDOW_STATE_CHANGED_EVENT: &str = "window-state-changed";
+
+/// Lowest opacity accepted, so the window can't be made invisible by accident.
+pub const MIN_OPACITY: f64 = 0.2;
+
+/// Picture-in-picture style state of the main window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct WindowState {
+    pub always_on_top: bool,
+    pub visible_on_all_workspaces: bool,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub opacity: f64,
+}
+
+fn current(app: &AppHandle) -> WindowState {
+    WindowState {
+        always_on_top: settings::get(app, settings::ALWAYS_ON_TOP).unwrap_or(false),
+        visible_on_all_workspaces: settings::get(app, settings::VISIBLE_ON_ALL_WORKSPACES)
+            .unwrap_or(false),
+        resizable: settings::get(app, settings::WINDOW_RESIZABLE).unwrap_or(true),
+        decorations: settings::get(app, settings::WINDOW_DECORATIONS).unwrap_or(true),
+        // Imports don't go through `set_window_opacity`'s check.
+        opacity: settings::get::<f64>(app, settings::WINDOW_OPACITY)
+            .unwrap_or(1.0)
+            .clamp(MIN_OPACITY, 1.0),
+    }
+}
+
+/// Applies the stored always-on-top, workspace, resizing, title bar and
+/// opacity settings to `window`.
+pub fn restore(window: &Window) {
+    let state = current(window.app_handle());
+    if state.always_on_top {
+        let _ = window.set_always_on_top(true);
+    }
+    if state.visible_on_all_workspaces {
+        set_on_all_workspaces(window, true);
+    }
+    if !state.resizable {
+        let _ = window.set_resizable(false);
+    }
+    if !state.decorations {
+        let _ = window.set_decorations(false);
+    }
+    if state.opacity < 1.0 {
+        set_opacity(window, state.opacity);
+    }
+}
+
+fn emit_changed(app: &AppHandle) {
+    let _ = app.emit_to(main_window::LABEL, WINDOW_STATE_CHANGED_EVENT, current(app));
+}
+
+/// Keeps the main window above all others.
+#[tauri::command]
+pub fn set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    main_window::window(&app)?
+        .set_always_on_top(enabled)
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    settings::update(&app, settings::ALWAYS_ON_TOP, enabled).map_err(AppError::Store)?;
+    emit_changed(&app);
+    Ok(())
+}
+
+/// Shows the main window on every virtual desktop. Only stored, with a
+/// warning, on Windows, which has no such option.
+#[tauri::command]
+pub fn set_visible_on_all_workspaces(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    set_on_all_workspaces(&main_window::window(&app)?, enabled);
+    settings::update(&app, settings::VISIBLE_ON_ALL_WORKSPACES, enabled)
+        .map_err(AppError::Store)?;
+    emit_changed(&app);
+    Ok(())
+}
+
+/// Pins the main window's size, for fixed layouts. A pinned size is not
+/// saved as the window's bounds.
+#[tauri::command]
+pub fn set_resizable(app: AppHandle, resizable: bool) -> Result<(), AppError> {
+    main_window::window(&app)?
+        .set_resizable(resizable)
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    settings::update(&app, settings::WINDOW_RESIZABLE, resizable).map_err(AppError::Store)?;
+    emit_changed(&app);
+    Ok(())
+}
+
+/// Shows or hides the main window's title bar and borders.
+#[tauri::command]
+pub fn set_decorations(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    main_window::window(&app)?
+        .set_decorations(enabled)
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    settings::update(&app, settings::WINDOW_DECORATIONS, enabled).map_err(AppError::Store)?;
+    emit_changed(&app);
+    Ok(())
+}
+
+/// Sets the main window's opacity, from 0.2 to 1.0. Ignored with a warning on
+/// platforms without window opacity support.
+#[tauri::command]
+pub fn set_window_opacity(app: AppHandle, opacity: f64) -> Result<(), AppError> {
+    if !(MIN_OPACITY..=1.0).contains(&opacity) {
+        return Err(AppError::InvalidInput(format!(
+            "opacity must be between {MIN_OPACITY} and 1.0"
+        )));
+    }
+    set_opacity(&main_window::window(&app)?, opacity);
+    settings::update(&app, settings::WINDOW_OPACITY, opacity).map_err(AppError::Store)?;
+    emit_changed(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_window_state(app: AppHandle) -> WindowState {
+    current(&app)
+}
+
+fn set_on_all_workspaces(window: &Window, enabled: bool) {
+    if cfg!(windows) {
+        log::warn!("showing the window on all workspaces isn't supported on this platform");
+        return;
+    }
+    if let Err(e) = window.set_visible_on_all_workspaces(enabled) {
+        log::warn!("failed to show the window on all workspaces: {e}");
+    }
+}
+
+/// Native window handles may only be touched on the main thread.
+fn set_opacity(window: &Window, opacity: f64) {
+    let target = window.clone();
+    let result = window.run_on_main_thread(move || {
+        if let Err(e) = native::set_opacity(&target, opacity) {
+            log::warn!("failed to set the window opacity: {e}");
+        }
+    });
+    if let Err(e) = result {
+        log::warn!("failed to set the window opacity: {e}");
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use ::windows::Win32::Foundation::COLORREF;
+    use ::windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+    use tauri::Window;
+
+    pub fn set_opacity(window: &Window, opacity: f64) -> Result<(), String> {
+        let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+        let alpha = (opacity * 255.0).round() as u8;
+        // SAFETY: `hwnd` belongs to a live window and we're on its thread.
+        unsafe {
+            let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+            SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc2_app_kit::NSWindow;
+    use tauri::Window;
+
+    pub fn set_opacity(window: &Window, opacity: f64) -> Result<(), String> {
+        let ns_window = window.ns_window().map_err(|e| e.to_string())?;
+        // SAFETY: tauri hands out the NSWindow backing this live window.
+        let ns_window = unsafe { &*ns_window.cast::<NSWindow>() };
+        ns_window.setAlphaValue(opacity);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use gtk::prelude::WidgetExt;
+    use tauri::Window;
+
+    pub fn set_opacity(window: &Window, opacity: f64) -> Result<(), String> {
+        window
+            .gtk_window()
+            .map_err(|e| e.to_string())?
+            .set_opacity(opacity);
+        Ok(())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+mod native {
+    use tauri::Window;
+
+    pub fn set_opacity(_window: &Window, _opacity: f64) -> Result<(), String> {
+        Err("window opacity isn't supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,158 @@
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::error::AppError;
+use crate::{bounds, route, splash, theme, zoom};
+
+/// Secondary windows must use this label prefix; the `desktop` capability
+/// grants IPC access to `main` and `window-*` only.
+const SECONDARY_PREFIX: &str = "window-";
+
+/// Broadcasts go out as `broadcast:<channel>`, so a window can't pass one off
+/// as an event from the app itself.
+const BROADCAST_PREFIX: &str = "broadcast:";
+const MAX_CHANNEL_LEN: usize = 64;
+/// Largest broadcast payload, as serialized JSON.
+const MAX_BROADCAST_BYTES: usize = 64 * 1024;
+
+/// What the windows listening on a broadcast channel receive.
+#[derive(Debug, Clone, Serialize)]
+pub struct BroadcastPayload {
+    /// Label of the window that sent it.
+    pub from: String,
+    pub payload: Value,
+}
+
+fn validate_channel(channel: &str) -> Result<(), AppError> {
+    let valid = !channel.is_empty()
+        && channel.len() <= MAX_CHANNEL_LEN
+        && channel
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "broadcast channel \"{channel}\" must be 1 to {MAX_CHANNEL_LEN} letters, digits, '-', '_', ':' or '/'"
+        )))
+    }
+}
+
+fn validate_label(label: &str) -> Result<(), String> {
+    let name = label.strip_prefix(SECONDARY_PREFIX).unwrap_or_default();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "window label \"{label}\" must be \"{SECONDARY_PREFIX}\" followed by letters, digits, '-' or '_'"
+        ))
+    }
+}
+
+/// Opens a secondary window on the app route `url`, or focuses the window if
+/// one with `label` is already open.
+#[tauri::command]
+pub fn open_window(app: AppHandle, label: String, url: String) -> Result<(), String> {
+    validate_label(&label)?;
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.unminimize();
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    let route = route::sanitize_route(&url).ok_or_else(|| format!("invalid route \"{url}\""))?;
+
+    let window = WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(route.into()))
+        .title("bolt.gives")
+        .inner_size(1280.0, 832.0)
+        .min_inner_size(960.0, 640.0)
+        .visible(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let _ = theme::apply(&window.as_ref().window());
+    let _ = bounds::restore(&window.as_ref().window());
+    zoom::restore(&window);
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Sends `payload` to every window listening on `broadcast:<channel>`,
+/// leaving out the sender with `exclude_self`. `from_label` has to be the
+/// calling window's label.
+#[tauri::command]
+pub fn broadcast(
+    app: AppHandle,
+    window: WebviewWindow,
+    channel: String,
+    payload: Value,
+    exclude_self: bool,
+    from_label: String,
+) -> Result<(), AppError> {
+    validate_channel(&channel)?;
+    if from_label != window.label() {
+        return Err(AppError::Forbidden(format!(
+            "window \"{}\" can't broadcast as \"{from_label}\"",
+            window.label()
+        )));
+    }
+    let size = serde_json::to_vec(&payload)
+        .map_err(|e| AppError::InvalidInput(e.to_string()))?
+        .len();
+    if size > MAX_BROADCAST_BYTES {
+        log::warn!("dropped a {size} byte broadcast on {channel} from {from_label}");
+        return Err(AppError::InvalidInput(format!(
+            "broadcast payloads are limited to {MAX_BROADCAST_BYTES} bytes, this one is {size}"
+        )));
+    }
+
+    let event = format!("{BROADCAST_PREFIX}{channel}");
+    let message = BroadcastPayload {
+        from: from_label,
+        payload,
+    };
+    for label in app.webview_windows().into_keys() {
+        if label == splash::LABEL || (exclude_self && label == message.from) {
+            continue;
+        }
+        if let Err(e) = app.emit_to(label.as_str(), &event, &message) {
+            log::warn!("failed to broadcast on {channel} to {label}: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_windows(app: AppHandle) -> Vec<String> {
+    let mut labels: Vec<String> = app
+        .webview_windows()
+        .into_keys()
+        .filter(|label| label != splash::LABEL)
+        .collect();
+    labels.sort();
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secondary_labels_need_the_prefix() {
+        assert!(validate_label("window-chat_2").is_ok());
+        assert!(validate_label("window-").is_err());
+        assert!(validate_label("main").is_err());
+        assert!(validate_label("chat").is_err());
+        assert!(validate_label("window-a/b").is_err());
+    }
+
+    #[test]
+    fn validates_broadcast_channels() {
+        assert!(validate_channel("chat/typing").is_ok());
+        assert!(validate_channel("").is_err());
+        assert!(validate_channel("a b").is_err());
+        assert!(validate_channel(&"c".repeat(MAX_CHANNEL_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::error::AppError;
+use crate::{main_window, settings};
+
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 3.0;
+/// Change per zoom in/out shortcut.
+const ZOOM_STEP: f64 = 0.1;
+
+/// Store key for the zoom of the window labelled `label`. The main window
+/// uses the unscoped key.
+fn store_key(label: &str) -> String {
+    if label == main_window::LABEL {
+        settings::ZOOM_LEVEL.to_string()
+    } else {
+        format!("{}/{label}", settings::ZOOM_LEVEL)
+    }
+}
+
+/// Limits `factor` to [`MIN_ZOOM`]..=[`MAX_ZOOM`], rounded to two decimals so
+/// repeated steps don't drift.
+fn clamp_zoom(factor: f64) -> f64 {
+    if !factor.is_finite() {
+        return 1.0;
+    }
+    (factor.clamp(MIN_ZOOM, MAX_ZOOM) * 100.0).round() / 100.0
+}
+
+fn current(window: &WebviewWindow) -> f64 {
+    settings::get::<f64>(window.app_handle(), &store_key(window.label()))
+        .map(clamp_zoom)
+        .unwrap_or(1.0)
+}
+
+/// Zooms `window` and persists the factor actually applied.
+fn apply(window: &WebviewWindow, factor: f64) -> Result<f64, AppError> {
+    let factor = clamp_zoom(factor);
+    window
+        .set_zoom(factor)
+        .map_err(|e| AppError::Window(e.to_string()))?;
+    settings::update(window.app_handle(), &store_key(window.label()), factor)
+        .map_err(AppError::Store)?;
+    Ok(factor)
+}
+
+/// Applies the persisted zoom to `window`, if it isn't the default.
+pub fn restore(window: &WebviewWindow) {
+    let factor = current(window);
+    if factor != 1.0 {
+        let _ = window.set_zoom(factor);
+    }
+}
+
+/// The focused window, falling back to the main one, for the zoom shortcuts.
+fn target(app: &AppHandle) -> Option<WebviewWindow> {
+    app.webview_windows()
+        .into_values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| app.get_webview_window(main_window::LABEL))
+}
+
+pub fn zoom_in(app: &AppHandle) {
+    if let Some(window) = target(app) {
+        let _ = apply(&window, current(&window) + ZOOM_STEP);
+    }
+}
+
+pub fn zoom_out(app: &AppHandle) {
+    if let Some(window) = target(app) {
+        let _ = apply(&window, current(&window) - ZOOM_STEP);
+    }
+}
+
+pub fn reset_zoom(app: &AppHandle) {
+    if let Some(window) = target(app) {
+        let _ = apply(&window, 1.0);
+    }
+}
+
+/// Zooms the calling window to `factor`, clamped to 0.5–3.0, and returns the
+/// factor applied.
+#[tauri::command]
+pub fn set_zoom(window: WebviewWindow, factor: f64) -> Result<f64, AppError> {
+    apply(&window, factor)
+}
+
+#[tauri::command]
+pub fn get_zoom(window: WebviewWindow) -> f64 {
+    current(&window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_zoom_to_the_supported_range() {
+        assert_eq!(clamp_zoom(1.25), 1.25);
+        assert_eq!(clamp_zoom(0.1), MIN_ZOOM);
+        assert_eq!(clamp_zoom(10.0), MAX_ZOOM);
+        assert_eq!(clamp_zoom(f64::NAN), 1.0);
+        assert_eq!(clamp_zoom(f64::INFINITY), 1.0);
+    }
+
+    #[test]
+    fn steps_land_on_round_values() {
+        let mut zoom = 1.0;
+        for _ in 0..3 {
+            zoom = clamp_zoom(zoom + ZOOM_STEP);
+        }
+        assert_eq!(zoom, 1.3);
+    }
+}